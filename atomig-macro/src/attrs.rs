@@ -0,0 +1,161 @@
+//! Parsing of the `#[atom(...)]` helper attributes.
+
+use proc_macro2::Span;
+use proc_macro_crate::FoundCrate;
+use syn::{spanned::Spanned, Attribute, Error, Expr, Ident, LitInt, LitStr, Path};
+
+
+/// Options specified via `#[atom(...)]` on the type definition itself.
+#[derive(Default)]
+pub(crate) struct TypeAttrs {
+    /// `#[atom(bitfield)]`: pack fields with explicit bit widths.
+    pub(crate) bitfield: Option<Span>,
+
+    /// `#[atom(repr = u32)]`: explicitly chosen `Repr` type.
+    pub(crate) repr: Option<Ident>,
+
+    /// `#[atom(fallback = Variant)]` or `#[atom(fallback = "path::to::fn")]`:
+    /// what `unpack` returns for invalid values instead of panicking.
+    pub(crate) fallback: Option<Fallback>,
+
+    /// `#[atom(with = "path::to::module")]`: module with `pack` and `unpack`
+    /// functions used for the conversion.
+    pub(crate) with: Option<Path>,
+
+    /// `#[atom(via = "into")]` or `#[atom(via = "try_from")]`: use existing
+    /// conversion impls between the type and `Repr`.
+    pub(crate) via: Option<LitStr>,
+
+    /// `#[atom(crate = "path::to::atomig")]`: path to the `atomig` crate.
+    pub(crate) krate: Option<Path>,
+
+    /// `#[atom(niche = 0xFF)]`: representation never returned by `pack`,
+    /// used to implement `AtomNiche`.
+    pub(crate) niche: Option<Expr>,
+}
+
+/// The value of a `fallback` attribute.
+#[derive(Clone)]
+pub(crate) enum Fallback {
+    /// A variant of the enum.
+    Variant(Ident),
+    /// A function `fn(Repr) -> Self`.
+    Fn(Path),
+}
+
+impl Fallback {
+    pub(crate) fn span(&self) -> Span {
+        match self {
+            Fallback::Variant(ident) => ident.span(),
+            Fallback::Fn(path) => path.span(),
+        }
+    }
+}
+
+impl TypeAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut out = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("atom")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bitfield") {
+                    out.bitfield = Some(meta.path.span());
+                } else if meta.path.is_ident("repr") {
+                    out.repr = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("fallback") {
+                    let value = meta.value()?;
+                    let fallback = if value.peek(LitStr) {
+                        Fallback::Fn(value.parse::<LitStr>()?.parse()?)
+                    } else {
+                        Fallback::Variant(value.parse()?)
+                    };
+                    out.fallback = Some(fallback);
+                } else if meta.path.is_ident("with") {
+                    out.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                } else if meta.path.is_ident("via") {
+                    out.via = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("crate") {
+                    out.krate = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                } else if meta.path.is_ident("niche") {
+                    out.niche = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unknown `atom` attribute for type definition"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the path to the `atomig` crate to use in generated code. If not
+    /// specified via `#[atom(crate = _)]`, it is looked up in the manifest of
+    /// the crate being compiled, which handles renamed dependencies.
+    pub(crate) fn crate_path(&self) -> Path {
+        if let Some(krate) = &self.krate {
+            return krate.clone();
+        }
+
+        let name = match proc_macro_crate::crate_name("atomig") {
+            Ok(FoundCrate::Name(name)) => name,
+            // `atomig`'s own tests and doc tests refer to it by name.
+            Ok(FoundCrate::Itself) | Err(_) => "atomig".into(),
+        };
+        Ident::new(&name, Span::call_site()).into()
+    }
+}
+
+/// Options specified via `#[atom(...)]` on a field.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    /// `#[atom(bits = 3)]`: width of this field in a bitfield struct.
+    pub(crate) bits: Option<LitInt>,
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut out = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("atom")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bits") {
+                    out.bits = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unknown `atom` attribute for field"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// Options specified via `#[atom(...)]` on an enum variant.
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    /// `#[atom(fallback)]`: `unpack` returns this variant for invalid values.
+    pub(crate) fallback: Option<Span>,
+
+    /// `#[atom(other)]`: this variant holds all raw values that do not belong
+    /// to any other variant.
+    pub(crate) other: Option<Span>,
+}
+
+impl VariantAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut out = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("atom")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("fallback") {
+                    out.fallback = Some(meta.path.span());
+                } else if meta.path.is_ident("other") {
+                    out.other = Some(meta.path.span());
+                } else {
+                    return Err(meta.error("unknown `atom` attribute for enum variant"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(out)
+    }
+}
@@ -0,0 +1,110 @@
+//! `derive(Atom)` via user-provided conversions (`with` and `via`).
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{spanned::Spanned, Data, DeriveInput, Error};
+
+use crate::{attrs::{Fallback, TypeAttrs}, enums};
+
+
+/// Generates the body of the `impl Atom` block for types with
+/// `#[atom(with = _)]` or `#[atom(via = _)]`.
+pub(crate) fn atom_impl_via_conversion(
+    input: &DeriveInput,
+    attrs: &TypeAttrs,
+) -> Result<TokenStream2, Error> {
+    let repr = match &attrs.repr {
+        Some(repr) => repr,
+        None => {
+            let msg = "`#[atom(with = _)]` and `#[atom(via = _)]` require specifying the \
+                representation via `#[atom(repr = _)]`";
+            return Err(Error::new(Span::call_site(), msg));
+        }
+    };
+    if let Some(span) = attrs.bitfield {
+        let msg = "`#[atom(bitfield)]` cannot be combined with `with` or `via`";
+        return Err(Error::new(span, msg));
+    }
+
+    let fallback = match &input.data {
+        Data::Enum(e) => enums::fallback(e, attrs)?,
+        _ => attrs.fallback.clone(),
+    };
+
+    // `with`: simply call the functions of the given module.
+    if let Some(module) = &attrs.with {
+        if let Some(via) = &attrs.via {
+            let msg = "`with` and `via` cannot be used at the same time";
+            return Err(Error::new(via.span(), msg));
+        }
+        if fallback.is_some() {
+            let msg = "a fallback has no effect with `#[atom(with = _)]`, handle invalid \
+                values in the module's `unpack` function instead";
+            return Err(Error::new(module.span(), msg));
+        }
+
+        return Ok(quote! {
+            type Repr = #repr;
+
+            fn pack(self) -> Self::Repr {
+                #module::pack(self)
+            }
+            fn unpack(src: Self::Repr) -> Self {
+                #module::unpack(src)
+            }
+        });
+    }
+
+    // `via`: use the `From`/`Into` or `TryFrom` impls.
+    let via = attrs.via.as_ref().expect("neither `with` nor `via` given");
+    let (unpack, try_unpack) = match via.value().as_str() {
+        "into" => {
+            if fallback.is_some() {
+                let msg = "a fallback has no effect with `#[atom(via = \"into\")]` as the \
+                    conversion cannot fail";
+                return Err(Error::new(via.span(), msg));
+            }
+            (quote! { ::core::convert::Into::<Self>::into(src) }, quote! {})
+        }
+        "try_from" => {
+            let error = format!(
+                "invalid '{}' value '{{}}' for type '{}' in `Atom::unpack`",
+                repr,
+                input.ident,
+            );
+            let on_error = match fallback {
+                Some(Fallback::Variant(variant)) => quote! { Self::#variant },
+                Some(Fallback::Fn(path)) => quote! { #path(src) },
+                None => quote! { panic!(#error, src) },
+            };
+            let unpack = quote! {
+                match <Self as ::core::convert::TryFrom<#repr>>::try_from(src) {
+                    ::core::result::Result::Ok(v) => v,
+                    ::core::result::Result::Err(_) => #on_error,
+                }
+            };
+            let try_unpack = quote! {
+                fn try_unpack(src: Self::Repr) -> ::core::option::Option<Self> {
+                    <Self as ::core::convert::TryFrom<#repr>>::try_from(src).ok()
+                }
+            };
+            (unpack, try_unpack)
+        }
+        _ => {
+            let msg = "`via` has to be either \"into\" or \"try_from\"";
+            return Err(Error::new(via.span(), msg));
+        }
+    };
+
+    Ok(quote! {
+        type Repr = #repr;
+
+        fn pack(self) -> Self::Repr {
+            ::core::convert::Into::<#repr>::into(self)
+        }
+        fn unpack(src: Self::Repr) -> Self {
+            #unpack
+        }
+        #try_unpack
+    })
+}
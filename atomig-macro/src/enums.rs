@@ -0,0 +1,696 @@
+//! `derive(Atom)` for enums.
+
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parenthesized, spanned::Spanned, DataEnum, DeriveInput, Error, Expr, Fields, Ident, Lit,
+    Path, Type, UnOp, Variant, BinOp,
+};
+
+use crate::{
+    attrs::{Fallback, TypeAttrs, VariantAttrs},
+    structs,
+};
+
+
+const INTEGER_NAMES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize",
+    "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// Generates the body of the `impl Atom` block for the given enum definition.
+pub(crate) fn atom_impl_for_enum(
+    input: &DeriveInput,
+    e: &DataEnum,
+    attrs: &TypeAttrs,
+) -> Result<TokenStream2, Error> {
+    // Open enums and enums with fields are handled separately.
+    let mut other = None;
+    for variant in &e.variants {
+        if let Some(span) = VariantAttrs::parse(&variant.attrs)?.other {
+            if other.is_some() {
+                return Err(Error::new(span, "only one variant can be marked `#[atom(other)]`"));
+            }
+            other = Some(variant);
+        }
+    }
+    if let Some(other) = other {
+        return atom_impl_for_open_enum(input, e, attrs, other);
+    }
+    if e.variants.iter().any(|variant| !matches!(variant.fields, Fields::Unit)) {
+        return atom_impl_for_data_enum(input, e, attrs);
+    }
+
+    let (repr_type, from_repr_attr) = resolve_repr(input, e, attrs)?;
+
+    // If the `Repr` type does not come from `#[repr(_)]`, we have to make sure
+    // that all discriminant values actually fit into it.
+    let type_name = &input.ident;
+    let fit_checks = if from_repr_attr {
+        quote! {}
+    } else {
+        let checks = e.variants.iter().map(|variant| {
+            let variant_name = &variant.ident;
+            let msg = format!(
+                "discriminant of variant '{}' does not fit into `{}`",
+                variant_name,
+                repr_type,
+            );
+            quote! {
+                assert!(
+                    #type_name::#variant_name as i128
+                        == #type_name::#variant_name as #repr_type as i128,
+                    #msg,
+                );
+            }
+        });
+        quote! {
+            const _: () = { #(#checks)* };
+        }
+    };
+
+    // Generate the code for `unpack`, which has to map each discriminant
+    // value back to its variant. This has to be fast even for enums with many
+    // variants, so we emit code that rustc can turn into a range check and a
+    // table lookup (or nothing at all, if `Repr` matches the enum's layout).
+    let error = format!(
+        "invalid '{}' value '{{}}' for enum '{}' in `Atom::unpack`",
+        repr_type,
+        type_name,
+    );
+    let on_invalid = match fallback(e, attrs)? {
+        Some(Fallback::Variant(variant)) => quote! { #type_name::#variant },
+        Some(Fallback::Fn(path)) => quote! { #path(src) },
+        None => quote! { panic!(#error, src) },
+    };
+    let try_unpack_code = gen_try_unpack(input, e, &repr_type);
+    let krate = attrs.crate_path();
+
+    Ok(quote! {
+        type Repr = #repr_type;
+
+        fn pack(self) -> Self::Repr {
+            #fit_checks
+            self as #repr_type
+        }
+        fn unpack(src: Self::Repr) -> Self {
+            match <Self as #krate::Atom>::try_unpack(src) {
+                ::core::option::Option::Some(v) => v,
+                ::core::option::Option::None => #on_invalid,
+            }
+        }
+        fn try_unpack(src: Self::Repr) -> ::core::option::Option<Self> {
+            #try_unpack_code
+        }
+    })
+}
+
+/// Generates the body of the `impl Atom` block for an "open" enum: all
+/// variants but `other` are unit variants and `other` holds every raw value
+/// that is not the discriminant of another variant.
+fn atom_impl_for_open_enum(
+    input: &DeriveInput,
+    e: &DataEnum,
+    attrs: &TypeAttrs,
+    other: &Variant,
+) -> Result<TokenStream2, Error> {
+    if let Some(param) = input.generics.params.first() {
+        let msg = "`derive(Atom)` does not support generic parameters for enums with fields";
+        return Err(Error::new(param.span(), msg));
+    }
+    if let Some(repr) = &attrs.repr {
+        let msg = "`#[atom(repr = _)]` is not supported for enums with an `#[atom(other)]` \
+            variant, the type of its field is used as representation";
+        return Err(Error::new(repr.span(), msg));
+    }
+    if fallback(e, attrs)?.is_some() {
+        let msg = "a fallback cannot be combined with an `#[atom(other)]` variant, as all \
+            values are valid";
+        return Err(Error::new(other.ident.span(), msg));
+    }
+
+    // The field of `other` determines the `Repr` type.
+    let field = match other.fields.iter().collect::<Vec<_>>()[..] {
+        [field] => field,
+        _ => {
+            let msg = "the `#[atom(other)]` variant must have exactly one field";
+            return Err(Error::new(other.span(), msg));
+        }
+    };
+    let repr_type = match &field.ty {
+        Type::Path(p) if p.qself.is_none() => p.path.get_ident().filter(|i| is_integer_name(i)),
+        _ => None,
+    };
+    let repr_type = match repr_type {
+        Some(ty) => ty,
+        None => {
+            let msg = "the field of the `#[atom(other)]` variant must be a primitive integer";
+            return Err(Error::new(field.ty.span(), msg));
+        }
+    };
+
+    if let Some(v) = e.variants.iter().find(|v| v.ident != other.ident && !v.fields.is_empty()) {
+        let msg = "only the `#[atom(other)]` variant of an open enum may have fields";
+        return Err(Error::new(v.span(), msg));
+    }
+    let values = match discriminant_values(e) {
+        Some(values) => values,
+        None => {
+            let msg = "all explicit discriminants have to be integer literals in enums with \
+                an `#[atom(other)]` variant";
+            return Err(Error::new(Span::call_site(), msg));
+        }
+    };
+
+    // Check that no two known variants share a value. As the `other` variant
+    // is never stored with its own discriminant, it is excluded.
+    let known = e.variants.iter()
+        .zip(values)
+        .filter(|(v, _)| v.ident != other.ident)
+        .collect::<Vec<_>>();
+    for (i, (variant, value)) in known.iter().enumerate() {
+        if let Some((prev, _)) = known[..i].iter().find(|(_, v)| v == value) {
+            let msg = format!(
+                "discriminant value {} of '{}' is already used by '{}'",
+                value,
+                variant.ident,
+                prev.ident,
+            );
+            return Err(Error::new(variant.span(), msg));
+        }
+    }
+
+    let type_name = &input.ident;
+    let other_name = &other.ident;
+    let other_value = match &field.ident {
+        Some(name) => quote! { #type_name::#other_name { #name: raw } },
+        None => quote! { #type_name::#other_name(raw) },
+    };
+    let fit_checks = known.iter().map(|(variant, value)| {
+        let value = Literal::i128_unsuffixed(*value);
+        let msg = format!(
+            "discriminant of variant '{}' does not fit into `{}`",
+            variant.ident,
+            repr_type,
+        );
+        quote! {
+            assert!(
+                #repr_type::MIN as i128 <= #value && #value <= #repr_type::MAX as i128,
+                #msg,
+            );
+        }
+    });
+    let pack_arms = known.iter().map(|(variant, value)| {
+        let variant_name = &variant.ident;
+        let value = Literal::i128_unsuffixed(*value);
+        quote! { #type_name::#variant_name => #value, }
+    });
+    let unpack_arms = known.iter().map(|(variant, value)| {
+        let variant_name = &variant.ident;
+        let value = Literal::i128_unsuffixed(*value);
+        quote! { #value => #type_name::#variant_name, }
+    });
+
+    Ok(quote! {
+        type Repr = #repr_type;
+
+        fn pack(self) -> Self::Repr {
+            const _: () = { #(#fit_checks)* };
+
+            match self {
+                #(#pack_arms)*
+                #other_value => raw,
+            }
+        }
+        fn unpack(src: Self::Repr) -> Self {
+            match src {
+                #(#unpack_arms)*
+                raw => #other_value,
+            }
+        }
+    })
+}
+
+/// Generates the body of the `impl Atom` block for an enum where variants
+/// have at most one field. The index of the variant is stored as tag in the
+/// high bits and the field's representation in the low bits.
+fn atom_impl_for_data_enum(
+    input: &DeriveInput,
+    e: &DataEnum,
+    attrs: &TypeAttrs,
+) -> Result<TokenStream2, Error> {
+    if let Some(param) = input.generics.params.first() {
+        let msg = "`derive(Atom)` does not support generic parameters for enums with fields";
+        return Err(Error::new(param.span(), msg));
+    }
+    if let Some(repr) = &attrs.repr {
+        let msg = "`#[atom(repr = _)]` is not supported for enums with fields, the \
+            representation is chosen automatically";
+        return Err(Error::new(repr.span(), msg));
+    }
+
+    // Collect the single field (if any) of each variant.
+    let krate = attrs.crate_path();
+    let mut variants = Vec::new();
+    for variant in &e.variants {
+        let mut fields = variant.fields.iter();
+        let field = fields.next();
+        if let Some(extra) = fields.next() {
+            let msg = "`derive(Atom)` only supports enum variants with at most one field";
+            return Err(Error::new(extra.span(), msg));
+        }
+        variants.push((variant, field));
+    }
+
+    let tag_bits = match variants.len() {
+        0 | 1 => 0,
+        n => usize::BITS - (n - 1).leading_zeros(),
+    };
+    let payload_bits = payload_bits(e, &krate);
+
+    let type_name = &input.ident;
+    let constructor = |variant: &Variant, value: TokenStream2| {
+        let ident = &variant.ident;
+        match &variant.fields {
+            Fields::Named(f) => {
+                let name = &f.named[0].ident;
+                quote! { #type_name::#ident { #name: #value } }
+            }
+            Fields::Unnamed(_) => quote! { #type_name::#ident(#value) },
+            Fields::Unit => quote! { #type_name::#ident },
+        }
+    };
+    let tagged = |tag: usize| if tag_bits == 0 {
+        quote! { 0 }
+    } else {
+        let tag = Literal::usize_unsuffixed(tag);
+        quote! { (#tag << PAYLOAD_BITS) }
+    };
+
+    let pack_arms = variants.iter().enumerate().map(|(i, (variant, field))| {
+        let tag = tagged(i);
+        match field {
+            Some(field) => {
+                let ty = &field.ty;
+                let pattern = constructor(variant, quote! { payload });
+                quote! {
+                    #pattern => #tag | #krate::impls::PrimitiveAtomBits::into_bits(
+                        <#ty as #krate::Atom>::pack(payload)
+                    ),
+                }
+            }
+            None => {
+                let pattern = constructor(variant, quote! {});
+                quote! { #pattern => #tag, }
+            }
+        }
+    });
+    // `unpack` and `try_unpack` only differ in how the field is unpacked and
+    // in wrapping the result in `Some`.
+    let unpack_arms = |fallible: bool| {
+        let (method, question_mark) = structs::unpack_method(fallible);
+        variants.iter().enumerate().map(|(i, (variant, field))| {
+            let i = Literal::usize_unsuffixed(i);
+            let value = match field {
+                Some(field) => {
+                    let ty = &field.ty;
+                    constructor(variant, quote! {
+                        <#ty as #krate::Atom>::#method(
+                            #krate::impls::PrimitiveAtomBits::from_bits(bits)
+                        ) #question_mark
+                    })
+                }
+                None => constructor(variant, quote! {}),
+            };
+            if fallible {
+                quote! { #i => ::core::option::Option::Some(#value), }
+            } else {
+                quote! { #i => #value, }
+            }
+        }).collect::<Vec<_>>()
+    };
+    let try_unpack_arms = unpack_arms(true);
+    let unpack_arms = unpack_arms(false);
+
+    let error = format!("invalid tag '{{}}' for enum '{}' in `Atom::unpack`", type_name);
+    let on_invalid = match fallback(e, attrs)? {
+        Some(Fallback::Variant(ident)) => {
+            let (variant, _) = variants.iter().find(|(v, _)| v.ident == ident).unwrap();
+            if !matches!(variant.fields, Fields::Unit) {
+                let msg = "the fallback variant must not have fields";
+                return Err(Error::new(ident.span(), msg));
+            }
+            quote! { #type_name::#ident }
+        }
+        Some(Fallback::Fn(path)) => quote! { #path(src) },
+        None => quote! { panic!(#error, tag) },
+    };
+    let tag = if tag_bits == 0 {
+        quote! { 0 }
+    } else {
+        quote! { bits >> PAYLOAD_BITS }
+    };
+    let fit_error = format!(
+        "enum '{}' does not fit into 64 bits: {} tag bits plus the largest field \
+            representation are too many",
+        type_name,
+        tag_bits,
+    );
+
+    Ok(quote! {
+        type Repr = <
+            #krate::impls::Bits<{ #tag_bits + #payload_bits }> as #krate::impls::SelectRepr
+        >::Repr;
+
+        fn pack(self) -> Self::Repr {
+            const PAYLOAD_BITS: u32 = #payload_bits;
+            const TAG_BITS: u32 = #tag_bits;
+            const _: () = assert!(TAG_BITS + PAYLOAD_BITS <= 64, #fit_error);
+
+            let bits: u64 = match self {
+                #(#pack_arms)*
+            };
+            #krate::impls::PrimitiveAtomBits::from_bits(bits)
+        }
+        fn unpack(src: Self::Repr) -> Self {
+            const PAYLOAD_BITS: u32 = #payload_bits;
+
+            let bits = #krate::impls::PrimitiveAtomBits::into_bits(src);
+            let tag = #tag;
+            match tag {
+                #(#unpack_arms)*
+                _ => #on_invalid,
+            }
+        }
+        fn try_unpack(src: Self::Repr) -> ::core::option::Option<Self> {
+            const PAYLOAD_BITS: u32 = #payload_bits;
+
+            let bits = #krate::impls::PrimitiveAtomBits::into_bits(src);
+            let tag = #tag;
+            match tag {
+                #(#try_unpack_arms)*
+                _ => ::core::option::Option::None,
+            }
+        }
+    })
+}
+
+/// Returns an expression for the number of bits of the largest field of an
+/// enum with fields, which are stored below the tag.
+fn payload_bits(e: &DataEnum, krate: &Path) -> TokenStream2 {
+    let widths = e.variants.iter().filter_map(|v| v.fields.iter().next()).map(|field| {
+        let ty = &field.ty;
+        quote! { <<#ty as #krate::Atom>::Repr as #krate::impls::PrimitiveAtomBits>::BITS }
+    });
+    quote! { #krate::impls::max_bits(&[#(#widths),*]) }
+}
+
+/// Checks that the niche specified via `#[atom(niche = _)]` is never returned
+/// by the derived `pack`. Open enums use all values and are rejected. For
+/// C-like enums, literal discriminants are compared with the niche. For enums
+/// with fields, a compile-time assertion is returned.
+pub(crate) fn niche_check(
+    input: &DeriveInput,
+    e: &DataEnum,
+    niche: &Expr,
+    krate: &Path,
+) -> Result<TokenStream2, Error> {
+    for variant in &e.variants {
+        if VariantAttrs::parse(&variant.attrs)?.other.is_some() {
+            let msg = "`#[atom(niche = _)]` is not supported for enums with an \
+                `#[atom(other)]` variant, as every value is a valid representation";
+            return Err(Error::new_spanned(niche, msg));
+        }
+    }
+
+    if e.variants.iter().all(|v| matches!(v.fields, Fields::Unit)) {
+        let niche_value = eval_int(niche);
+        if let (Some(niche_value), Some(values)) = (niche_value, discriminant_values(e)) {
+            if let Some(i) = values.iter().position(|v| *v == niche_value) {
+                let msg = format!(
+                    "niche {} is the discriminant of variant '{}'",
+                    niche_value,
+                    e.variants[i].ident,
+                );
+                return Err(Error::new_spanned(niche, msg));
+            }
+        }
+        return Ok(quote! {});
+    }
+
+    let type_name = &input.ident;
+    let payload_bits = payload_bits(e, krate);
+    let num_variants = e.variants.len() as u64;
+    let unit_tags = e.variants.iter()
+        .enumerate()
+        .filter(|(_, v)| matches!(v.fields, Fields::Unit))
+        .map(|(i, _)| i as u64);
+    let msg = format!("the niche of enum '{}' can be returned by `Atom::pack`", type_name);
+    Ok(quote! {
+        const _: () = assert!(
+            #krate::impls::niche_unused(
+                <#type_name as #krate::AtomNiche>::NICHE as u64,
+                #payload_bits,
+                #num_variants,
+                &[#(#unit_tags),*],
+            ),
+            #msg,
+        );
+    })
+}
+
+/// Generates the body of `try_unpack` for C-like enums. Returns `None` for
+/// values that do not correspond to any variant.
+fn gen_try_unpack(input: &DeriveInput, e: &DataEnum, repr_type: &Ident) -> TokenStream2 {
+    let type_name = &input.ident;
+
+    // If we don't know the discriminant values, we can still use a `match`
+    // with constants as patterns.
+    let values = match discriminant_values(e) {
+        Some(values) => values,
+        None => {
+            let consts = e.variants.iter().enumerate().map(|(i, variant)| {
+                let name = format_ident!("V{}", i);
+                let variant_name = &variant.ident;
+                quote! { const #name: #repr_type = #type_name::#variant_name as #repr_type; }
+            });
+            let arms = e.variants.iter().enumerate().map(|(i, variant)| {
+                let name = format_ident!("V{}", i);
+                let variant_name = &variant.ident;
+                quote! { #name => ::core::option::Option::Some(#type_name::#variant_name), }
+            });
+            return quote! {
+                #(#consts)*
+                match src {
+                    #(#arms)*
+                    _ => ::core::option::Option::None,
+                }
+            };
+        }
+    };
+
+    let mut sorted: Vec<_> = values.iter().copied().zip(&e.variants).collect();
+    sorted.sort_by_key(|(value, _)| *value);
+    let contiguous = sorted.windows(2).all(|w| w[0].0 + 1 == w[1].0);
+
+    if contiguous && !sorted.is_empty() {
+        // Subtract the smallest discriminant and do a range check on the
+        // resulting index. The `match` on the index is then compiled to a
+        // table lookup. The last variant is covered by `_` so that we don't
+        // need an unreachable arm.
+        let unsigned = format_ident!("{}", match repr_type.to_string().as_str() {
+            "u8" | "i8" => "u8",
+            "u16" | "i16" => "u16",
+            "u32" | "i32" => "u32",
+            "u64" | "i64" => "u64",
+            "u128" | "i128" => "u128",
+            _ => "usize",
+        });
+        let start = Literal::i128_unsuffixed(sorted[0].0);
+        let last_index = Literal::usize_unsuffixed(sorted.len() - 1);
+        let (last, init) = sorted.split_last().unwrap();
+        let arms = init.iter().enumerate().map(|(i, (_, variant))| {
+            let i = Literal::usize_unsuffixed(i);
+            let variant_name = &variant.ident;
+            quote! { #i => #type_name::#variant_name, }
+        });
+        let last_variant = &last.1.ident;
+        quote! {
+            const START: #repr_type = #start;
+            let index = (src as #unsigned).wrapping_sub(START as #unsigned);
+            if index <= #last_index {
+                return ::core::option::Option::Some(match index {
+                    #(#arms)*
+                    _ => #type_name::#last_variant,
+                });
+            }
+            ::core::option::Option::None
+        }
+    } else {
+        let arms = sorted.iter().map(|(value, variant)| {
+            let value = Literal::i128_unsuffixed(*value);
+            let variant_name = &variant.ident;
+            quote! { #value => ::core::option::Option::Some(#type_name::#variant_name), }
+        });
+        quote! {
+            match src {
+                #(#arms)*
+                _ => ::core::option::Option::None,
+            }
+        }
+    }
+}
+
+/// Returns the fallback for invalid values, specified either on the enum or
+/// on one of its variants.
+pub(crate) fn fallback(e: &DataEnum, attrs: &TypeAttrs) -> Result<Option<Fallback>, Error> {
+    let mut out = None;
+    for variant in &e.variants {
+        if let Some(span) = VariantAttrs::parse(&variant.attrs)?.fallback {
+            if out.is_some() || attrs.fallback.is_some() {
+                return Err(Error::new(span, "only one fallback can be specified"));
+            }
+            out = Some(Fallback::Variant(variant.ident.clone()));
+        }
+    }
+
+    if let Some(Fallback::Variant(ident)) = &attrs.fallback {
+        if !e.variants.iter().any(|v| v.ident == *ident) {
+            let msg = format!("fallback '{}' is not a variant of this enum", ident);
+            return Err(Error::new(ident.span(), msg));
+        }
+    }
+
+    Ok(attrs.fallback.clone().or(out))
+}
+
+/// Determines the `Repr` type of a C-like enum. It is either given via
+/// `#[atom(repr = _)]`, via `#[repr(_)]` or inferred from the discriminant
+/// values. The returned `bool` is `true` if it matches the `#[repr(_)]`
+/// attribute.
+pub(crate) fn resolve_repr(
+    input: &DeriveInput,
+    e: &DataEnum,
+    attrs: &TypeAttrs,
+) -> Result<(Ident, bool), Error> {
+    let repr_attr = repr_from_attr(input)?;
+    let repr_type = match (&attrs.repr, &repr_attr) {
+        (Some(repr), _) => {
+            if !is_integer_name(repr) {
+                let msg = "`#[atom(repr = _)]` has to specify a primitive integer type";
+                return Err(Error::new(repr.span(), msg));
+            }
+            repr.clone()
+        }
+        (None, Some(repr)) => repr.clone(),
+        (None, None) => infer_repr(input, e)?,
+    };
+    let from_repr_attr = repr_attr.as_ref() == Some(&repr_type);
+
+    Ok((repr_type, from_repr_attr))
+}
+
+/// Returns the primitive integer type from the `#[repr(_)]` attribute, if
+/// specified. Other representation hints, like in `#[repr(C, u8)]`, are
+/// ignored.
+fn repr_from_attr(input: &DeriveInput) -> Result<Option<Ident>, Error> {
+    let mut repr_type = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            match meta.path.get_ident() {
+                Some(ident) if is_integer_name(ident) => repr_type = Some(ident.clone()),
+                // Skip the arguments of hints like `align(4)`.
+                _ if meta.input.peek(syn::token::Paren) => {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    content.parse::<TokenStream2>()?;
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(repr_type)
+}
+
+/// Infers the smallest integer type that can represent all discriminant
+/// values. Fails if not all discriminants are integer literals.
+fn infer_repr(input: &DeriveInput, e: &DataEnum) -> Result<Ident, Error> {
+    let values = discriminant_values(e).ok_or_else(|| {
+        let msg = format!(
+            "cannot infer the representation of enum '{}' because not all discriminants \
+                are integer literals. Please add a `#[repr(_)]` or `#[atom(repr = _)]` \
+                attribute.",
+            input.ident,
+        );
+        Error::new(Span::call_site(), msg)
+    })?;
+
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    let fits = |lo: i128, hi: i128| min >= lo && max <= hi;
+    let name = if min >= 0 {
+        if fits(0, u8::MAX.into()) {
+            "u8"
+        } else if fits(0, u16::MAX.into()) {
+            "u16"
+        } else if fits(0, u32::MAX.into()) {
+            "u32"
+        } else {
+            "u64"
+        }
+    } else if fits(i8::MIN.into(), i8::MAX.into()) {
+        "i8"
+    } else if fits(i16::MIN.into(), i16::MAX.into()) {
+        "i16"
+    } else if fits(i32::MIN.into(), i32::MAX.into()) {
+        "i32"
+    } else {
+        "i64"
+    };
+
+    Ok(Ident::new(name, Span::call_site()))
+}
+
+/// Returns the discriminant values of all variants, in order, or `None` if not
+/// all of them can be determined. Values can only be determined if all
+/// explicit discriminants are integer literals (optionally negated or
+/// shifted).
+pub(crate) fn discriminant_values(e: &DataEnum) -> Option<Vec<i128>> {
+    let mut next = 0;
+    e.variants.iter().map(|variant| {
+        let value = match &variant.discriminant {
+            Some((_, expr)) => eval_int(expr)?,
+            None => next,
+        };
+        next = value.checked_add(1)?;
+        Some(value)
+    }).collect()
+}
+
+/// Evaluates an integer literal expression like `3`, `-0x10`, `(7u8)` or
+/// `1 << 4`.
+fn eval_int(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => int.base10_parse().ok(),
+            _ => None,
+        },
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => {
+            eval_int(&unary.expr)?.checked_neg()
+        }
+        Expr::Binary(bin) if matches!(bin.op, BinOp::Shl(_)) => {
+            let value = eval_int(&bin.left)?;
+            let shift = u32::try_from(eval_int(&bin.right)?).ok()?;
+            value.checked_shl(shift).filter(|v| v >> shift == value)
+        }
+        Expr::Paren(paren) => eval_int(&paren.expr),
+        Expr::Group(group) => eval_int(&group.expr),
+        _ => None,
+    }
+}
+
+fn is_integer_name(ident: &Ident) -> bool {
+    INTEGER_NAMES.iter().any(|int| ident == int)
+}
@@ -0,0 +1,179 @@
+//! `derive(AtomFlags)` for C-like enums with power-of-two discriminants.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, Data, DeriveInput, Error, Fields};
+
+use crate::{attrs::TypeAttrs, enums};
+
+
+/// Generates the set type `{Name}Set` with its `Atom` and `AtomLogic` impls
+/// and the `impl AtomFlags` block for the given enum.
+pub(crate) fn gen_flags_impl(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let e = match &input.data {
+        Data::Enum(e) => e,
+        _ => {
+            let msg = "`AtomFlags` can only be derived for C-like enums";
+            return Err(Error::new(Span::call_site(), msg));
+        }
+    };
+    if let Some(param) = input.generics.params.first() {
+        let msg = "`derive(AtomFlags)` does not support generic parameters";
+        return Err(Error::new(param.span(), msg));
+    }
+    if let Some(v) = e.variants.iter().find(|v| !matches!(v.fields, Fields::Unit)) {
+        let msg = "this variant has fields, but `derive(AtomFlags)` only works for C-like enums";
+        return Err(Error::new(v.span(), msg));
+    }
+
+    // All flags have to be distinct single bits, which we can only check if
+    // the discriminants are literals.
+    let values = enums::discriminant_values(e).ok_or_else(|| {
+        let msg = "all discriminants have to be integer literals for `derive(AtomFlags)`";
+        Error::new(Span::call_site(), msg)
+    })?;
+    for (i, (variant, value)) in e.variants.iter().zip(&values).enumerate() {
+        if *value <= 0 || (value & (value - 1)) != 0 {
+            let msg = format!(
+                "discriminant {} of flag '{}' is not a power of two",
+                value,
+                variant.ident,
+            );
+            return Err(Error::new(variant.span(), msg));
+        }
+        if let Some(prev) = values[..i].iter().position(|v| v == value) {
+            let msg = format!(
+                "flag '{}' overlaps with flag '{}' (both are {})",
+                variant.ident,
+                e.variants[prev].ident,
+                value,
+            );
+            return Err(Error::new(variant.span(), msg));
+        }
+    }
+
+    let attrs = TypeAttrs::parse(&input.attrs)?;
+    let krate = attrs.crate_path();
+    let (repr, _) = enums::resolve_repr(input, e, &attrs)?;
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let set_name = format_ident!("{}Set", name);
+    let flags = e.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+    let set_doc = format!("A set of [`{}`] flags, generated by `derive(AtomFlags)`.", name);
+    let const_new = crate::gen_const_new(&set_name, &repr, quote! { self.0 }, &krate);
+
+    Ok(quote! {
+        #[doc = #set_doc]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+        #vis struct #set_name(#repr);
+
+        #[allow(dead_code)]
+        impl #set_name {
+            /// The set without any flags.
+            #vis const EMPTY: Self = #set_name(0);
+
+            /// The set containing all flags.
+            #vis const ALL: Self = #set_name(0 #(| #name::#flags as #repr)*);
+
+            /// Returns the raw bits of this set.
+            #vis const fn bits(self) -> #repr {
+                self.0
+            }
+
+            /// Adds `flag` to this set.
+            #vis fn insert(&mut self, flag: #name) {
+                self.0 |= flag as #repr;
+            }
+
+            /// Removes `flag` from this set.
+            #vis fn remove(&mut self, flag: #name) {
+                self.0 &= !(flag as #repr);
+            }
+
+            /// Returns `true` if this set contains `flag`.
+            #vis fn contains(&self, flag: #name) -> bool {
+                (self.0 & flag as #repr) != 0
+            }
+
+            /// Returns `true` if this set does not contain any flag.
+            #vis fn is_empty(&self) -> bool {
+                (self.0 & Self::ALL.0) == 0
+            }
+
+            /// Returns an iterator over all flags in this set, in declaration
+            /// order.
+            #vis fn iter(&self) -> impl Iterator<Item = #name> {
+                let set = *self;
+                ::core::iter::IntoIterator::into_iter([#(#name::#flags),*])
+                    .filter(move |flag| set.contains(*flag))
+            }
+        }
+
+        impl ::core::convert::From<#name> for #set_name {
+            fn from(flag: #name) -> Self {
+                #set_name(flag as #repr)
+            }
+        }
+
+        impl ::core::iter::FromIterator<#name> for #set_name {
+            fn from_iter<I: ::core::iter::IntoIterator<Item = #name>>(iter: I) -> Self {
+                let mut out = Self::EMPTY;
+                for flag in iter {
+                    out.insert(flag);
+                }
+                out
+            }
+        }
+
+        impl ::core::ops::BitOr for #set_name {
+            type Output = Self;
+            fn bitor(self, rhs: Self) -> Self {
+                #set_name(self.0 | rhs.0)
+            }
+        }
+
+        impl ::core::ops::BitAnd for #set_name {
+            type Output = Self;
+            fn bitand(self, rhs: Self) -> Self {
+                #set_name(self.0 & rhs.0)
+            }
+        }
+
+        impl ::core::ops::Not for #set_name {
+            type Output = Self;
+            fn not(self) -> Self {
+                #set_name(!self.0 & Self::ALL.0)
+            }
+        }
+
+        impl #krate::Atom for #set_name {
+            type Repr = #repr;
+            fn pack(self) -> Self::Repr {
+                self.0
+            }
+            fn unpack(src: Self::Repr) -> Self {
+                #set_name(src)
+            }
+        }
+
+        impl #krate::AtomLogic for #set_name {
+            const NOT_MASK: #repr = Self::ALL.0;
+        }
+
+        #const_new
+
+        impl #krate::AtomFlags for #name {
+            type Set = #set_name;
+            fn into_set(self) -> Self::Set {
+                #set_name(self as #repr)
+            }
+            fn complement_set(self) -> Self::Set {
+                !#set_name(self as #repr)
+            }
+            fn is_in(self, set: Self::Set) -> bool {
+                set.contains(self)
+            }
+        }
+    })
+}
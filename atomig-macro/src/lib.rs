@@ -2,25 +2,35 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
-use quote::quote;
-use syn::{
-    parse_macro_input, spanned::Spanned, Data, DataEnum, DataStruct, DeriveInput, Error, Fields,
-};
+use quote::{format_ident, quote, ToTokens};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Path};
+
+use crate::attrs::TypeAttrs;
+
+mod attrs;
+mod convert;
+mod enums;
+mod flags;
+mod structs;
 
 
 /// Custom derive for the `Atom` trait. Please see the trait's documentation
 /// for more information on this derive.
-#[proc_macro_derive(Atom)]
+#[proc_macro_derive(Atom, attributes(atom))]
 pub fn derive_atom(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     gen_atom_impl(&input)
+        .and_then(|mut out| {
+            out.extend(gen_niche_impl(&input)?);
+            Ok(out)
+        })
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
 
 /// Custom derive for the `AtomLogic` trait. Please see the trait's
 /// documentation for more information on this derive.
-#[proc_macro_derive(AtomLogic)]
+#[proc_macro_derive(AtomLogic, attributes(atom))]
 pub fn derive_atom_logic(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     gen_marker_trait_impl("AtomLogic", &input)
@@ -30,7 +40,7 @@ pub fn derive_atom_logic(input: TokenStream) -> TokenStream {
 
 /// Custom derive for the `AtomInteger` trait. Please see the trait's
 /// documentation for more information on this derive.
-#[proc_macro_derive(AtomInteger)]
+#[proc_macro_derive(AtomInteger, attributes(atom))]
 pub fn derive_atom_integer(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     gen_marker_trait_impl("AtomInteger", &input)
@@ -38,15 +48,122 @@ pub fn derive_atom_integer(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Custom derive for the `AtomFloat` trait. Please see the trait's
+/// documentation for more information on this derive.
+#[proc_macro_derive(AtomFloat, attributes(atom))]
+pub fn derive_atom_float(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    gen_marker_trait_impl("AtomFloat", &input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Custom derive for the `AtomFlags` trait. Please see the trait's
+/// documentation for more information on this derive.
+#[proc_macro_derive(AtomFlags, attributes(atom))]
+pub fn derive_atom_flags(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    flags::gen_flags_impl(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
 fn gen_marker_trait_impl(trait_name: &str, input: &DeriveInput) -> Result<TokenStream2, Error> {
-    match input.data {
-        Data::Struct(_) => {
+    match &input.data {
+        Data::Struct(s) => {
+            // For packed structs, logical operations on the representation are
+            // performed on all fields at the same time. That's only fine if
+            // all fields are fine with that. Integer operations would carry
+            // over into neighboring fields, so those are never fine.
+            let attrs = TypeAttrs::parse(&input.attrs)?;
+            let krate = attrs.crate_path();
+            let fields = structs::atom_fields(s);
+            let mut generics = input.generics.clone();
+            let mut field_checks = quote! {};
+            let mut items = quote! {};
+            if fields.len() > 1 || attrs.bitfield.is_some() {
+                if trait_name != "AtomLogic" {
+                    let msg = format!(
+                        "`{}` cannot be derived for structs with multiple fields or \
+                            bitfield structs",
+                        trait_name,
+                    );
+                    return Err(Error::new(Span::call_site(), msg));
+                }
+
+                // Logical operations like `fetch_not` also change unused bits
+                // of the representation, which `unpack` ignores. Values would
+                // then not survive a round trip, so compare-exchange loops
+                // could spin forever.
+                let field_types = fields.iter().map(|(_, f)| &f.ty);
+                let layout_bits = structs::layout_bits(s, &attrs, &krate)?;
+                let type_name = &input.ident;
+                let msg = format!(
+                    "`AtomLogic` can only be derived if the fields of `{}` fill its whole \
+                        representation",
+                    type_name,
+                );
+                field_checks = quote! {
+                    const _: fn() = || {
+                        fn assert_atom_logic<T: #krate::AtomLogic>()
+                        where
+                            T::Repr: #krate::impls::PrimitiveAtomLogic,
+                        {}
+                        #( assert_atom_logic::<#field_types>(); )*
+                    };
+                    const _: () = assert!(
+                        #layout_bits == <<#type_name as #krate::Atom>::Repr
+                            as #krate::impls::PrimitiveAtomBits>::BITS,
+                        #msg,
+                    );
+                };
+
+                // Each field only allows its own `NOT_MASK` to be flipped.
+                let not_mask = structs::not_mask(s, &attrs, &krate)?;
+                items = quote! {
+                    const NOT_MASK: <Self as #krate::Atom>::Repr =
+                        (#not_mask) as <Self as #krate::Atom>::Repr;
+                };
+            } else if let Some((_, field)) = fields.first() {
+                // For generic newtypes, the field has to implement the trait
+                // as well.
+                let ty = &field.ty;
+                if structs::mentions_type_param(&input.generics, ty) {
+                    let trait_name = Ident::new(trait_name, Span::call_site());
+                    let primitive = format_ident!("Primitive{}", trait_name);
+                    let predicates = &mut generics.make_where_clause().predicates;
+                    predicates.push(parse_quote! { #ty: #krate::#trait_name });
+                    predicates.push(parse_quote! {
+                        <#ty as #krate::Atom>::Repr: #krate::impls::#primitive
+                    });
+                }
+
+                // Newtypes share the representation of their field, so they
+                // also take over the field's constants of the trait.
+                if attrs.with.is_none() && attrs.via.is_none() {
+                    items = match trait_name {
+                        "AtomLogic" => quote! {
+                            const NOT_MASK: <Self as #krate::Atom>::Repr =
+                                <#ty as #krate::AtomLogic>::NOT_MASK;
+                        },
+                        "AtomInteger" => quote! {
+                            const NON_ZERO: bool = <#ty as #krate::AtomInteger>::NON_ZERO;
+                        },
+                        _ => quote! {},
+                    };
+                }
+            }
+
             let type_name = &input.ident;
             let trait_name = Ident::new(trait_name, Span::call_site());
-            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
             Ok(quote! {
-                impl #impl_generics atomig::#trait_name
-                    for #type_name #ty_generics #where_clause {}
+                #field_checks
+
+                impl #impl_generics #krate::#trait_name
+                    for #type_name #ty_generics #where_clause {
+                    #items
+                }
             })
         }
         Data::Enum(_) => {
@@ -68,154 +185,113 @@ fn gen_marker_trait_impl(trait_name: &str, input: &DeriveInput) -> Result<TokenS
 
 /// The actual implementation for `derive(Atom)`.
 fn gen_atom_impl(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let attrs = TypeAttrs::parse(&input.attrs)?;
+    let krate = attrs.crate_path();
+
+    // Reject options that do not apply to the selected layout.
+    if attrs.with.is_none() && attrs.via.is_none() {
+        match &input.data {
+            Data::Struct(s) => structs::check_attrs(s, &attrs)?,
+            Data::Enum(_) => if let Some(span) = attrs.bitfield {
+                let msg = "`#[atom(bitfield)]` is only supported for structs";
+                return Err(Error::new(span, msg));
+            },
+            Data::Union(_) => {}
+        }
+    }
+
     // Generate the body of the impl block.
     let impl_body = match &input.data {
-        Data::Struct(s) => atom_impl_for_struct(s),
-        Data::Enum(e) => atom_impl_for_enum(input, e),
+        _ if attrs.with.is_some() || attrs.via.is_some() => {
+            convert::atom_impl_via_conversion(input, &attrs)
+        }
+        Data::Struct(s) if attrs.bitfield.is_some() => {
+            return structs::atom_impl_for_bitfield(input, s, &attrs);
+        }
+        Data::Struct(s) if structs::atom_fields(s).len() > 1 => {
+            return structs::atom_impl_for_packed_struct(input, s, &attrs);
+        }
+        Data::Struct(s) => return structs::atom_impl_for_struct(input, s, &attrs),
+        Data::Enum(e) => enums::atom_impl_for_enum(input, e, &attrs),
         Data::Union(_) => Err(Error::new(Span::call_site(), "unions cannot derive `Atom`")),
     }?;
 
+    // C-like enums can be packed in const context.
+    let const_new = match &input.data {
+        Data::Enum(e) if attrs.with.is_none()
+            && attrs.via.is_none()
+            && input.generics.params.is_empty()
+            && e.variants.iter().all(|v| matches!(v.fields, Fields::Unit)) =>
+        {
+            let (repr, _) = enums::resolve_repr(input, e, &attrs)?;
+            gen_const_new(&input.ident, &repr, quote! { self as #repr }, &krate)
+        }
+        _ => quote! {},
+    };
+
     // Combine everything into a finshed impl block.
     let type_name = &input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     Ok(quote! {
-        impl #impl_generics atomig::Atom for #type_name #ty_generics #where_clause {
+        impl #impl_generics #krate::Atom for #type_name #ty_generics #where_clause {
             #impl_body
         }
+
+        #const_new
     })
 }
 
-/// Generates the body of the `impl Atom` block for the given struct definition.
-fn atom_impl_for_struct(s: &DataStruct) -> Result<TokenStream2, Error> {
-    let mut it = s.fields.iter();
-
-    // Get first field
-    let field = it.next().ok_or_else(|| {
-        let msg = "struct has no fields, but `derive(Atom)` works only for \
-            structs with exactly one field";
-        Error::new(s.fields.span(), msg)
-    })?;
-
-    // Make sure there are no other fields
-    if it.next().is_some() {
-        let msg = "struct has more than one field, but `derive(Atom)` works only for \
-            structs with exactly one field";
-        return Err(Error::new(s.fields.span(), msg));
-    }
+/// Generates the `AtomNiche` impl if `#[atom(niche = _)]` is specified.
+fn gen_niche_impl(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let attrs = TypeAttrs::parse(&input.attrs)?;
+    let niche = match &attrs.niche {
+        Some(niche) => niche,
+        None => return Ok(quote! {}),
+    };
 
-    // Generate the code for `pack` and `unpack` which depends on whether it is
-    // a named or tuple-struct field.
-    let (field_access, struct_init) = match &field.ident {
-        Some(name) => (quote! { self.#name }, quote! { Self { #name: src } }),
-        None => (quote! { self.0 }, quote!{ Self(src) }),
+    // Check that `pack` cannot return the niche where the layout is known.
+    // For single-field structs and conversions, this is up to the user.
+    let krate = attrs.crate_path();
+    let check = match &input.data {
+        _ if attrs.with.is_some() || attrs.via.is_some() => quote! {},
+        Data::Enum(e) => enums::niche_check(input, e, niche, &krate)?,
+        Data::Struct(s) if attrs.bitfield.is_some() || structs::atom_fields(s).len() > 1 => {
+            structs::niche_check(input, s, &attrs, &krate)?
+        }
+        _ => quote! {},
     };
 
-    let field_type = &field.ty;
+    let type_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     Ok(quote! {
-        type Repr = <#field_type as atomig::Atom>::Repr;
-
-        fn pack(self) -> Self::Repr {
-            <#field_type as atomig::Atom>::pack(#field_access)
-        }
-        fn unpack(src: Self::Repr) -> Self {
-            let src = <#field_type as atomig::Atom>::unpack(src);
-            #struct_init
+        impl #impl_generics #krate::AtomNiche for #type_name #ty_generics #where_clause {
+            const NICHE: <Self as #krate::Atom>::Repr = #niche;
         }
+
+        #check
     })
 }
 
-/// Generates the body of the `impl Atom` block for the given enum definition.
-fn atom_impl_for_enum(input: &DeriveInput, e: &DataEnum) -> Result<TokenStream2, Error> {
-    const INTEGER_NAMES: &[&str] = &[
-        "u8", "u16", "u32", "u64", "u128", "usize",
-        "i8", "i16", "i32", "i64", "i128", "isize",
-    ];
-
-    let mut repr_type = None;
-    for attr in &input.attrs {
-        // Make sure we have a `repr` attribute on the enum.
-        if attr.path().is_ident("repr") {
-            // Make sure the `repr` attribute has the correct syntax and actually
-            // specifies the primitive representation.
-            attr.parse_nested_meta(|meta| {
-                repr_type = Some(
-                    meta.path
-                        .get_ident()
-                        .filter(|ident| INTEGER_NAMES.iter().any(|int| ident == int))
-                        .ok_or_else(|| {
-                            let msg = "`repr(_)` attribute does not specify the primitive \
-                            representation (a primitive integer), but this is required \
-                            for `derive(Atom)`";
-                            Error::new(meta.input.span(), msg)
-                        })?
-                        .clone(),
-                );
-
-                Ok(())
-            })?
-        }
-    }
-
-    let repr_type = repr_type.ok_or_else(|| {
-        let msg = format!(
-            "no `repr(_)` attribute on enum '{}', but such an attribute is \
-                required to automatically derive `Atom`",
-            input.ident,
-        );
-        Error::new(Span::call_site(), msg)
-    })?;
-
-    // Check that all variants have no fields. In other words: that the enum is
-    // C-like.
-    let variant_with_fields = e.variants.iter().find(|variant| {
-        match variant.fields {
-            Fields::Unit => false,
-            _ => true,
-        }
-    });
-    if let Some(v) = variant_with_fields  {
-        let msg = "this variant has fields, but `derive(Atom)` only works \
-            for C-like enums";
-        return Err(Error::new(v.span(), msg));
-    }
-
-    // Generate the code for `unpack` which is more complicated than the `pack`
-    // code. For `pack` we can simply use the `as` cast, but for unpack we have
-    // to assemble a list of `if` statements. If you would hand code such a
-    // method, you would use a `match` statement. But we use 'ifs' so that we
-    // don't have to check for the discriminant values ourselves. That might be
-    // very hard.
-    let type_name = &input.ident;
-    let unpack_code = {
-        let checks: Vec<_> = e.variants.iter().map(|variant| {
-            let variant_name = &variant.ident;
-            quote! {
-                if src == #type_name::#variant_name as #repr_type {
-                    return #type_name::#variant_name;
-                }
+/// Generates a hidden inherent `const fn` that creates an `Atomic<Self>` from
+/// `self`, which is what `atomig::atomic!` calls. `pack` has to be a const
+/// expression converting `self` to `repr`. Only works for types without
+/// generic parameters.
+pub(crate) fn gen_const_new(
+    type_name: &Ident,
+    repr: &dyn ToTokens,
+    pack: TokenStream2,
+    krate: &Path,
+) -> TokenStream2 {
+    quote! {
+        impl #type_name {
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            pub const fn __atomig_new_atomic(self) -> #krate::Atomic<Self> {
+                #krate::Atomic::from_impl(
+                    <<#repr as #krate::impls::PrimitiveAtom>::Impl>::new(#pack)
+                )
             }
-        }).collect();
-
-        let error = format!(
-            "invalid '{}' value '{{}}' for enum '{}' in `Atom::unpack`",
-            repr_type,
-            type_name,
-        );
-        quote! {
-            #(#checks)*
-            panic!(#error, src);
-        }
-    };
-
-
-    Ok(quote! {
-        type Repr = #repr_type;
-
-        fn pack(self) -> Self::Repr {
-            self as #repr_type
         }
-        fn unpack(src: Self::Repr) -> Self {
-            #unpack_code
-        }
-    })
+    }
 }
+
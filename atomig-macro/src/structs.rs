@@ -0,0 +1,627 @@
+//! `derive(Atom)` for structs.
+
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_quote, spanned::Spanned, DataStruct, DeriveInput, Error, Field, Generics, Ident,
+    Member, Path, Type, Visibility,
+};
+
+use crate::attrs::{FieldAttrs, TypeAttrs};
+
+
+/// Returns an error if `#[atom(...)]` options are given that do not apply to
+/// the layout of this struct. Not used for `with` and `via`, which ignore the
+/// fields.
+pub(crate) fn check_attrs(s: &DataStruct, attrs: &TypeAttrs) -> Result<(), Error> {
+    let multiple_fields = atom_fields(s).len() > 1;
+    if attrs.bitfield.is_none() {
+        // Field widths are only used by bitfield structs.
+        for (_, field) in members(s) {
+            if let Some(bits) = FieldAttrs::parse(&field.attrs)?.bits {
+                let msg = "`#[atom(bits = _)]` is only supported in structs with \
+                    `#[atom(bitfield)]`";
+                return Err(Error::new(bits.span(), msg));
+            }
+        }
+
+        if let Some(repr) = &attrs.repr {
+            let msg = if multiple_fields {
+                "`#[atom(repr = _)]` is not supported for structs with multiple \
+                    fields, the representation is chosen automatically (or use \
+                    `#[atom(bitfield, repr = _)]`)"
+            } else {
+                "`#[atom(repr = _)]` is not supported for structs with a single \
+                    field, the `Repr` of the field is used"
+            };
+            return Err(Error::new(repr.span(), msg));
+        }
+    }
+
+    if let Some(fallback) = &attrs.fallback {
+        let kind = match () {
+            _ if attrs.bitfield.is_some() => "bitfield structs",
+            _ if multiple_fields => "structs with multiple fields",
+            _ => "structs with a single field",
+        };
+        let msg = format!("a fallback is not supported for {}", kind);
+        return Err(Error::new(fallback.span(), msg));
+    }
+
+    Ok(())
+}
+
+/// Generates the `impl Atom` block for a struct with a single field (not
+/// counting phantom fields).
+pub(crate) fn atom_impl_for_struct(
+    input: &DeriveInput,
+    s: &DataStruct,
+    attrs: &TypeAttrs,
+) -> Result<TokenStream2, Error> {
+    let krate = attrs.crate_path();
+
+    // Get the only field
+    let fields = atom_fields(s);
+    let (member, field) = fields.first().ok_or_else(|| {
+        let msg = "struct has no fields (except phantom fields), but `derive(Atom)` works \
+            only for structs with at least one field";
+        Error::new(s.fields.span(), msg)
+    })?;
+
+    // If the field type depends on generic parameters, we require it to be
+    // `Atom`.
+    let field_type = &field.ty;
+    let mut generics = input.generics.clone();
+    if mentions_type_param(&input.generics, field_type) {
+        generics.make_where_clause().predicates.push(parse_quote! {
+            #field_type: #krate::Atom
+        });
+    }
+
+    // Newtypes around primitives can be packed in const context.
+    let const_new = match &field.ty {
+        Type::Path(p) if input.generics.params.is_empty() && is_primitive(&p.path) => {
+            crate::gen_const_new(&input.ident, field_type, quote! { self.#member }, &krate)
+        }
+        _ => quote! {},
+    };
+
+    let type_name = &input.ident;
+    let phantom_inits = phantom_inits(s);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #const_new
+
+        impl #impl_generics #krate::Atom for #type_name #ty_generics #where_clause {
+            type Repr = <#field_type as #krate::Atom>::Repr;
+
+            fn pack(self) -> Self::Repr {
+                <#field_type as #krate::Atom>::pack(self.#member)
+            }
+            fn unpack(src: Self::Repr) -> Self {
+                Self {
+                    #member: <#field_type as #krate::Atom>::unpack(src),
+                    #(#phantom_inits ,)*
+                }
+            }
+            fn try_unpack(src: Self::Repr) -> ::core::option::Option<Self> {
+                ::core::option::Option::Some(Self {
+                    #member: <#field_type as #krate::Atom>::try_unpack(src)?,
+                    #(#phantom_inits ,)*
+                })
+            }
+        }
+    })
+}
+
+/// Generates the `impl Atom` block for a struct with multiple fields. All
+/// fields are packed into the smallest primitive integer that can hold all
+/// their representations. Additionally, a trait with per-field helper methods
+/// for `Atomic<Self>` is generated.
+pub(crate) fn atom_impl_for_packed_struct(
+    input: &DeriveInput,
+    s: &DataStruct,
+    attrs: &TypeAttrs,
+) -> Result<TokenStream2, Error> {
+    if let Some(param) = input.generics.params.first() {
+        let msg = "`derive(Atom)` does not support generic parameters for structs \
+            with more than one field";
+        return Err(Error::new(param.span(), msg));
+    }
+
+    // The expression for the bit offset of each field inside the packed
+    // representation, plus the total number of bits as last element.
+    let krate = attrs.crate_path();
+    let fields = atom_fields(s);
+    let bits_of = |ty: &Type| quote! {
+        <<#ty as #krate::Atom>::Repr as #krate::impls::PrimitiveAtomBits>::BITS
+    };
+    let offsets: Vec<_> = (0..=fields.len()).map(|i| {
+        let bits = fields[..i].iter().map(|(_, f)| bits_of(&f.ty));
+        quote! { 0 #(+ #bits)* }
+    }).collect();
+    let total_bits = &offsets[fields.len()];
+
+    let pack_fields = fields.iter().zip(&offsets).map(|((member, field), offset)| {
+        let ty = &field.ty;
+        quote! {
+            bits |= #krate::impls::PrimitiveAtomBits::into_bits(
+                <#ty as #krate::Atom>::pack(self.#member)
+            ) << (#offset);
+        }
+    });
+    let unpack_fields = |fallible: bool| {
+        let (method, question_mark) = unpack_method(fallible);
+        fields.iter().zip(&offsets).map(|((member, field), offset)| {
+            let ty = &field.ty;
+            quote! {
+                #member: <#ty as #krate::Atom>::#method(
+                    #krate::impls::PrimitiveAtomBits::from_bits(bits >> (#offset))
+                ) #question_mark
+            }
+        }).collect::<Vec<_>>()
+    };
+    let try_unpack_fields = unpack_fields(true);
+    let unpack_fields = unpack_fields(false);
+
+    let type_name = &input.ident;
+    let phantom_inits = phantom_inits(s);
+    let fields_trait = gen_field_helpers(input, &fields, &krate);
+    let fit_error = format!(
+        "the fields of struct '{}' do not fit into 64 bits: their representations \
+            are too large in total",
+        type_name,
+    );
+    Ok(quote! {
+        const _: () = assert!(#total_bits <= 64, #fit_error);
+
+        impl #krate::Atom for #type_name {
+            type Repr =
+                <#krate::impls::Bits<{ #total_bits }> as #krate::impls::SelectRepr>::Repr;
+
+            fn pack(self) -> Self::Repr {
+                let mut bits: u64 = 0;
+                #(#pack_fields)*
+                #krate::impls::PrimitiveAtomBits::from_bits(bits)
+            }
+            fn unpack(src: Self::Repr) -> Self {
+                let bits = #krate::impls::PrimitiveAtomBits::into_bits(src);
+                Self {
+                    #(#unpack_fields ,)*
+                    #(#phantom_inits ,)*
+                }
+            }
+            fn try_unpack(src: Self::Repr) -> ::core::option::Option<Self> {
+                let bits = #krate::impls::PrimitiveAtomBits::into_bits(src);
+                ::core::option::Option::Some(Self {
+                    #(#try_unpack_fields ,)*
+                    #(#phantom_inits ,)*
+                })
+            }
+        }
+
+        #fields_trait
+    })
+}
+
+/// Generates the `impl Atom` block for a struct with `#[atom(bitfield)]`.
+/// Each field has an explicit width in bits and all fields are packed into
+/// `Repr` in declaration order, starting at the least significant bit.
+pub(crate) fn atom_impl_for_bitfield(
+    input: &DeriveInput,
+    s: &DataStruct,
+    attrs: &TypeAttrs,
+) -> Result<TokenStream2, Error> {
+    if let Some(param) = input.generics.params.first() {
+        let msg = "`derive(Atom)` does not support generic parameters for bitfield structs";
+        return Err(Error::new(param.span(), msg));
+    }
+
+    // Determine the width and offset of each field.
+    let krate = attrs.crate_path();
+    let fields = atom_fields(s);
+    let mut layout = Vec::new();
+    let mut offset = 0;
+    for (member, field) in &fields {
+        let width = bitfield_width(field)?;
+        layout.push((member, field, width, offset));
+        offset += width;
+
+        if offset > 64 {
+            let msg = format!(
+                "bitfield layout overflows: this field ends at bit {}, but at most 64 bits \
+                    are supported",
+                offset,
+            );
+            return Err(Error::new(field.span(), msg));
+        }
+    }
+
+    // Select the representation or check that the layout fits into the
+    // explicitly specified one.
+    let total_bits = offset;
+    let repr = match &attrs.repr {
+        Some(repr) => {
+            let repr_bits = match repr.to_string().as_str() {
+                "u8" => 8,
+                "u16" => 16,
+                "u32" => 32,
+                "u64" => 64,
+                _ => {
+                    let msg = "`repr` of a bitfield struct must be `u8`, `u16`, `u32` or `u64`";
+                    return Err(Error::new(repr.span(), msg));
+                }
+            };
+            if let Some((_, field, _, _)) = layout.iter().find(|(_, _, w, o)| w + o > repr_bits) {
+                let msg = format!(
+                    "bitfield layout overflows: this field does not fit into `{}` anymore \
+                        (the layout needs {} bits in total)",
+                    repr,
+                    total_bits,
+                );
+                return Err(Error::new(field.span(), msg));
+            }
+            repr.clone()
+        }
+        None => {
+            let name = match total_bits {
+                0..=8 => "u8",
+                9..=16 => "u16",
+                17..=32 => "u32",
+                _ => "u64",
+            };
+            Ident::new(name, input.ident.span())
+        }
+    };
+
+    let width_checks = layout.iter().map(|(_, field, width, _)| {
+        let ty = &field.ty;
+        let msg = format!(
+            "`#[atom(bits = {})]` is wider than the atomic representation of `{}`",
+            width,
+            quote!(#ty),
+        );
+        quote! {
+            const _: () = assert!(
+                #width <= <<#ty as #krate::Atom>::Repr as #krate::impls::PrimitiveAtomBits>::BITS,
+                #msg,
+            );
+        }
+    });
+    let pack_fields = layout.iter().map(|(member, field, width, offset)| {
+        let ty = &field.ty;
+        quote! {
+            bits |= #krate::impls::pack_bits(<#ty as #krate::Atom>::pack(self.#member), #width)
+                << #offset;
+        }
+    });
+    let unpack_fields = |fallible: bool| {
+        let (method, question_mark) = unpack_method(fallible);
+        layout.iter().map(|(member, field, width, offset)| {
+            let ty = &field.ty;
+            quote! {
+                #member: <#ty as #krate::Atom>::#method(
+                    #krate::impls::unpack_bits(bits >> #offset, #width)
+                ) #question_mark
+            }
+        }).collect::<Vec<_>>()
+    };
+    let try_unpack_fields = unpack_fields(true);
+    let unpack_fields = unpack_fields(false);
+
+    let type_name = &input.ident;
+    let phantom_inits = phantom_inits(s);
+    let fields_trait = gen_field_helpers(input, &fields, &krate);
+    Ok(quote! {
+        #(#width_checks)*
+
+        impl #krate::Atom for #type_name {
+            type Repr = #repr;
+
+            fn pack(self) -> Self::Repr {
+                let mut bits: u64 = 0;
+                #(#pack_fields)*
+                bits as #repr
+            }
+            fn unpack(src: Self::Repr) -> Self {
+                let bits = src as u64;
+                Self {
+                    #(#unpack_fields ,)*
+                    #(#phantom_inits ,)*
+                }
+            }
+            fn try_unpack(src: Self::Repr) -> ::core::option::Option<Self> {
+                let bits = src as u64;
+                ::core::option::Option::Some(Self {
+                    #(#try_unpack_fields ,)*
+                    #(#phantom_inits ,)*
+                })
+            }
+        }
+
+        #fields_trait
+    })
+}
+
+/// Returns the width of a field of a bitfield struct: the value of its
+/// `#[atom(bits = _)]` attribute or 1 for `bool` fields.
+fn bitfield_width(field: &Field) -> Result<u32, Error> {
+    match FieldAttrs::parse(&field.attrs)?.bits {
+        Some(lit) => {
+            let width = lit.base10_parse::<u32>()?;
+            if width == 0 || width > 64 {
+                return Err(Error::new(lit.span(), "bit width must be between 1 and 64"));
+            }
+            Ok(width)
+        }
+        None if is_bool(&field.ty) => Ok(1),
+        None => {
+            let msg = "missing `#[atom(bits = _)]` attribute: all non-`bool` fields \
+                of a bitfield struct need an explicit bit width";
+            Err(Error::new(field.span(), msg))
+        }
+    }
+}
+
+/// Returns const expressions for the width and offset of each field of a
+/// struct with multiple fields or a bitfield struct.
+fn field_layout<'a>(
+    s: &'a DataStruct,
+    attrs: &TypeAttrs,
+    krate: &Path,
+) -> Result<Vec<(&'a Field, TokenStream2, TokenStream2)>, Error> {
+    let mut offset = quote! { 0 };
+    atom_fields(s).into_iter().map(|(_, field)| {
+        let width = if attrs.bitfield.is_some() {
+            let width = bitfield_width(field)?;
+            quote! { #width }
+        } else {
+            let ty = &field.ty;
+            quote! { <<#ty as #krate::Atom>::Repr as #krate::impls::PrimitiveAtomBits>::BITS }
+        };
+        let field_offset = offset.clone();
+        offset = quote! { #offset + #width };
+        Ok((field, width, field_offset))
+    }).collect()
+}
+
+/// Returns a const expression for the number of bits used by the fields of a
+/// struct with multiple fields or a bitfield struct.
+pub(crate) fn layout_bits(
+    s: &DataStruct,
+    attrs: &TypeAttrs,
+    krate: &Path,
+) -> Result<TokenStream2, Error> {
+    let widths = field_layout(s, attrs, krate)?.into_iter().map(|(_, width, _)| width);
+    Ok(quote! { 0 #(+ #widths)* })
+}
+
+/// Returns a const expression for the `u64` bits of `AtomLogic::NOT_MASK` of
+/// a struct with multiple fields or a bitfield struct: the `NOT_MASK` of each
+/// field, shifted to the field's offset.
+pub(crate) fn not_mask(
+    s: &DataStruct,
+    attrs: &TypeAttrs,
+    krate: &Path,
+) -> Result<TokenStream2, Error> {
+    let masks = field_layout(s, attrs, krate)?.into_iter().map(|(field, width, offset)| {
+        let ty = &field.ty;
+        quote! {
+            (((<#ty as #krate::AtomLogic>::NOT_MASK as u64) & #krate::impls::mask(#width))
+                << (#offset))
+        }
+    });
+    Ok(quote! { 0 #(| #masks)* })
+}
+
+/// Returns a compile-time assertion that the niche specified via
+/// `#[atom(niche = _)]` is never returned by `pack` of a packed or bitfield
+/// struct, i.e. that it has bits set above all fields.
+pub(crate) fn niche_check(
+    input: &DeriveInput,
+    s: &DataStruct,
+    attrs: &TypeAttrs,
+    krate: &Path,
+) -> Result<TokenStream2, Error> {
+    let type_name = &input.ident;
+    let layout_bits = layout_bits(s, attrs, krate)?;
+    let msg = format!(
+        "the niche of struct '{}' can be returned by `Atom::pack`: it has to set a bit \
+            above all fields",
+        type_name,
+    );
+    Ok(quote! {
+        const _: () = assert!(
+            match (<#type_name as #krate::AtomNiche>::NICHE as u64).checked_shr(#layout_bits) {
+                ::core::option::Option::Some(high) => high != 0,
+                ::core::option::Option::None => false,
+            },
+            #msg,
+        );
+    })
+}
+
+/// Returns the `Atom` method used to unpack fields and the tokens following
+/// the call: `unpack` or `try_unpack` followed by `?`.
+pub(crate) fn unpack_method(fallible: bool) -> (TokenStream2, TokenStream2) {
+    if fallible {
+        (quote! { try_unpack }, quote! { ? })
+    } else {
+        (quote! { unpack }, quote! {})
+    }
+}
+
+/// Returns all fields of the struct together with the `Member` to access
+/// them.
+fn members(s: &DataStruct) -> Vec<(Member, &Field)> {
+    s.fields.iter().enumerate().map(|(i, field)| {
+        let member = match &field.ident {
+            Some(name) => Member::Named(name.clone()),
+            None => Member::Unnamed(i.into()),
+        };
+        (member, field)
+    }).collect()
+}
+
+/// Returns the fields that are stored in the atomic representation, i.e. all
+/// fields except phantom fields, together with the `Member` to access them.
+pub(crate) fn atom_fields(s: &DataStruct) -> Vec<(Member, &Field)> {
+    members(s).into_iter().filter(|(_, field)| !is_phantom(&field.ty)).collect()
+}
+
+/// Returns the initializers for all phantom fields, which are simply set to
+/// their default value when unpacking.
+fn phantom_inits(s: &DataStruct) -> Vec<TokenStream2> {
+    members(s).into_iter()
+        .filter(|(_, field)| is_phantom(&field.ty))
+        .map(|(member, _)| quote! { #member: ::core::default::Default::default() })
+        .collect()
+}
+
+/// Returns `true` for zero-sized marker types that do not carry any data:
+/// `PhantomData<_>`, `PhantomPinned` and `()`.
+fn is_phantom(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) if p.qself.is_none() => p.path.segments.last()
+            .is_some_and(|seg| seg.ident == "PhantomData" || seg.ident == "PhantomPinned"),
+        Type::Tuple(t) => t.elems.is_empty(),
+        Type::Group(g) => is_phantom(&g.elem),
+        Type::Paren(p) => is_phantom(&p.elem),
+        _ => false,
+    }
+}
+
+/// Returns `true` if `ty` mentions any of the type parameters of `generics`.
+pub(crate) fn mentions_type_param(generics: &Generics, ty: &Type) -> bool {
+    fn contains(tokens: TokenStream2, params: &[&Ident]) -> bool {
+        tokens.into_iter().any(|tt| match tt {
+            TokenTree::Ident(ident) => params.contains(&&ident),
+            TokenTree::Group(group) => contains(group.stream(), params),
+            _ => false,
+        })
+    }
+
+    let params = generics.type_params().map(|p| &p.ident).collect::<Vec<_>>();
+    !params.is_empty() && contains(ty.to_token_stream(), &params)
+}
+
+/// Returns `true` if the path is a primitive integer type or `bool`, i.e. a
+/// type whose `Repr` is itself.
+fn is_primitive(path: &Path) -> bool {
+    const NAMES: &[&str] = &[
+        "bool", "u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize",
+    ];
+    NAMES.iter().any(|name| path.is_ident(name))
+}
+
+/// Returns `true` if the type is literally `bool`.
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(p) if p.qself.is_none() && p.path.is_ident("bool"))
+}
+
+/// Generates a trait `Atomic{Name}Fields` with a `load_{field}` and
+/// `fetch_update_{field}` method for each field and implements it for
+/// `Atomic<Name>`. Methods are only generated for fields that are at least as
+/// visible as the struct itself.
+fn gen_field_helpers(
+    input: &DeriveInput,
+    fields: &[(Member, &Field)],
+    krate: &Path,
+) -> TokenStream2 {
+    let type_name = &input.ident;
+    let vis = &input.vis;
+    let trait_name = format_ident!("Atomic{}Fields", type_name);
+
+    let mut decls = Vec::new();
+    let mut impls = Vec::new();
+    for (member, field) in fields {
+        if !at_least_as_visible(&field.vis, vis) {
+            continue;
+        }
+
+        let ty = &field.ty;
+        let suffix = match member {
+            Member::Named(name) => name.to_string(),
+            Member::Unnamed(idx) => idx.index.to_string(),
+        };
+        let load = format_ident!("load_{}", suffix.trim_start_matches("r#"));
+        let update = format_ident!("fetch_update_{}", suffix.trim_start_matches("r#"));
+        let load_doc = format!("Loads the value and returns its field `{}`.", suffix);
+        let update_doc = format!(
+            "Like `Atomic::fetch_update`, but `f` only receives and replaces \
+                field `{}`. Returns the previous value of that field.",
+            suffix,
+        );
+
+        decls.push(quote! {
+            #[doc = #load_doc]
+            fn #load(&self, order: #krate::Ordering) -> #ty;
+
+            #[doc = #update_doc]
+            fn #update<F>(
+                &self,
+                set_order: #krate::Ordering,
+                fetch_order: #krate::Ordering,
+                f: F,
+            ) -> ::core::result::Result<#ty, #ty>
+            where
+                F: FnMut(#ty) -> ::core::option::Option<#ty>;
+        });
+        impls.push(quote! {
+            fn #load(&self, order: #krate::Ordering) -> #ty {
+                self.load(order).#member
+            }
+
+            fn #update<F>(
+                &self,
+                set_order: #krate::Ordering,
+                fetch_order: #krate::Ordering,
+                mut f: F,
+            ) -> ::core::result::Result<#ty, #ty>
+            where
+                F: FnMut(#ty) -> ::core::option::Option<#ty>,
+            {
+                self.fetch_update(set_order, fetch_order, |mut v| {
+                    v.#member = f(v.#member)?;
+                    ::core::option::Option::Some(v)
+                })
+                    .map(|v| v.#member)
+                    .map_err(|v| v.#member)
+            }
+        });
+    }
+
+    let doc = format!("Field helpers for `Atomic<{}>`, generated by `derive(Atom)`.", type_name);
+    quote! {
+        #[doc = #doc]
+        #vis trait #trait_name {
+            #(#decls)*
+        }
+
+        impl #trait_name for #krate::Atomic<#type_name> {
+            #(#impls)*
+        }
+    }
+}
+
+/// Returns `true` if a field with visibility `field` is at least as visible as
+/// its struct with visibility `ty`. If that cannot be decided syntactically
+/// (e.g. for different `pub(in path)`), `false` is returned.
+fn at_least_as_visible(field: &Visibility, ty: &Visibility) -> bool {
+    let restricted_to = |vis: &Visibility, name: &str| match vis {
+        Visibility::Restricted(r) => r.path.is_ident(name),
+        _ => false,
+    };
+    let is_private = |vis: &Visibility| {
+        matches!(vis, Visibility::Inherited) || restricted_to(vis, "self")
+    };
+
+    match (field, ty) {
+        (Visibility::Public(_), _) => true,
+        _ if is_private(ty) => true,
+        (_, Visibility::Public(_)) => false,
+        _ if restricted_to(field, "crate") => true,
+        _ => quote!(#field).to_string() == quote!(#ty).to_string(),
+    }
+}
+
@@ -25,6 +25,15 @@ struct BitSet(u16);
 #[derive(Debug, PartialEq, Atom)]
 struct Port(NonZeroU16);
 
+// Multiple fields are packed into one integer (`u32` in this case, as the
+// fields need 16 + 8 + 1 bits).
+#[derive(Debug, PartialEq, Atom)]
+struct Connection {
+    port: Port,
+    retries: u8,
+    open: bool,
+}
+
 fn main() {
     // Example with `Animal`
     let animal = Arc::new(Atomic::new(Animal::Cat));
@@ -53,14 +62,24 @@ fn main() {
     {
         let integer_set = integer_set.clone();
         thread::spawn(move || {
-            let mut current = 1;
+            let mut current = 0;
             while integer_set.load(Ordering::SeqCst) != BitSet(0xFFFF) {
-                integer_set.fetch_or(BitSet(current), Ordering::SeqCst);
-                current <<= 1;
+                integer_set.fetch_bit_set(current, Ordering::SeqCst);
+                current += 1;
             }
             println!("Set all bits!");
         });
     }
     thread::sleep(Duration::from_millis(10));
     println!("{:b}", integer_set.load(Ordering::SeqCst).0);
+
+
+    // Example with `Connection`. The derive also generates the trait
+    // `AtomicConnectionFields` with helpers to access single fields.
+    let port = Port(NonZeroU16::new(8080).unwrap());
+    let conn = Atomic::new(Connection { port, retries: 0, open: false });
+    let _: fn(Connection) -> u32 = Connection::pack; // `Repr` is `u32`
+    let _ = conn.fetch_update_retries(Ordering::SeqCst, Ordering::SeqCst, |r| Some(r + 1));
+    println!("Retries: {}", conn.load_retries(Ordering::SeqCst));
+    println!("Connection: {:?}", conn);
 }
@@ -0,0 +1,158 @@
+//! Float wrappers whose atomic representation preserves the total order.
+
+use core::{cmp, fmt, hash::{Hash, Hasher}, sync::atomic::Ordering};
+use crate::{
+    impls::{
+        self, AtomArithmetic, FloatArithmetic, PrimitiveAtom, PrimitiveAtomInteger,
+        PrimitiveAtomLogic,
+    },
+    Atom,
+};
+
+
+type Impl<R> = <R as PrimitiveAtom>::Impl;
+
+macro_rules! total_float {
+    ($name:ident, $float:ident, $repr:ident) => {
+        #[doc = concat!("An `", stringify!($float), "` ordered by IEEE 754 `totalOrder`,")]
+        #[doc = concat!("whose atomic representation (`", stringify!($repr), "`) has the same")]
+        /// order.
+        ///
+        /// The representation is the float's bits with the sign bit flipped
+        /// for positive numbers and all bits flipped for negative numbers.
+        /// Comparing these representations as unsigned integers yields
+        /// `totalOrder`: `-NaN < -inf < ... < -0.0 < +0.0 < ... < +inf <
+        /// +NaN`. Thanks to that, [`Atomic::fetch_max`][crate::Atomic::fetch_max]
+        /// and [`Atomic::fetch_min`][crate::Atomic::fetch_min] are single
+        /// atomic integer operations instead of compare-exchange loops.
+        /// `fetch_add` and `fetch_sub` are available as well, but they are
+        /// compare-exchange loops.
+        ///
+        #[doc = concat!("`Eq`, `Ord` and `Hash` are implemented via `", stringify!($float),
+            "::total_cmp`, so")]
+        /// `-0.0` and `+0.0` are different values and NaNs are equal to
+        /// themselves.
+        ///
+        /// ```
+        #[doc = concat!("use atomig::{Atomic, Ordering, ", stringify!($name), "};")]
+        ///
+        #[doc = concat!("let high = Atomic::new(", stringify!($name), "(0.25));")]
+        #[doc = concat!("high.fetch_max(", stringify!($name), "(1.5), Ordering::Relaxed);")]
+        #[doc = concat!("high.fetch_max(", stringify!($name), "(-3.0), Ordering::Relaxed);")]
+        #[doc = concat!("assert_eq!(high.load(Ordering::Relaxed), ", stringify!($name),
+            "(1.5));")]
+        /// ```
+        #[derive(Clone, Copy, Default)]
+        pub struct $name(pub $float);
+
+        impl $name {
+            const SIGN_BIT: $repr = 1 << ($repr::BITS - 1);
+
+            fn encode(self) -> $repr {
+                let bits = self.0.to_bits();
+                if bits & Self::SIGN_BIT == 0 { bits | Self::SIGN_BIT } else { !bits }
+            }
+
+            fn decode(src: $repr) -> Self {
+                let bits = if src & Self::SIGN_BIT == 0 { !src } else { src & !Self::SIGN_BIT };
+                Self($float::from_bits(bits))
+            }
+
+            fn fetch_float_op(
+                imp: &Impl<$repr>,
+                val: Self,
+                order: Ordering,
+                op: fn($float, $float) -> $float,
+            ) -> Self {
+                let f = |cur| Self(op(Self::decode(cur).0, val.0)).encode();
+                Self::decode(impls::fetch_op(imp, order, f))
+            }
+        }
+
+        impl Atom for $name {
+            type Repr = $repr;
+            fn pack(self) -> Self::Repr {
+                self.encode()
+            }
+            fn unpack(src: Self::Repr) -> Self {
+                Self::decode(src)
+            }
+        }
+
+        impl AtomArithmetic<FloatArithmetic> for $name {
+            fn fetch_add(imp: &Impl<$repr>, val: Self, order: Ordering) -> Self {
+                Self::fetch_float_op(imp, val, order, |a, b| a + b)
+            }
+            fn fetch_sub(imp: &Impl<$repr>, val: Self, order: Ordering) -> Self {
+                Self::fetch_float_op(imp, val, order, |a, b| a - b)
+            }
+            fn fetch_max(imp: &Impl<$repr>, val: Self, order: Ordering) -> Self {
+                Self::decode(<$repr as PrimitiveAtomInteger>::fetch_max(imp, val.encode(), order))
+            }
+            fn fetch_min(imp: &Impl<$repr>, val: Self, order: Ordering) -> Self {
+                Self::decode(<$repr as PrimitiveAtomInteger>::fetch_min(imp, val.encode(), order))
+            }
+            fn fetch_mul(imp: &Impl<$repr>, val: Self, order: Ordering) -> Self {
+                Self::fetch_float_op(imp, val, order, |a, b| a * b)
+            }
+            fn fetch_div(imp: &Impl<$repr>, val: Self, order: Ordering) -> Self {
+                Self::fetch_float_op(imp, val, order, |a, b| a / b)
+            }
+            fn fetch_rem(imp: &Impl<$repr>, val: Self, order: Ordering) -> Self {
+                Self::fetch_float_op(imp, val, order, |a, b| a % b)
+            }
+            fn fetch_neg(imp: &Impl<$repr>, order: Ordering) -> Self {
+                // Negating flips the sign bit of the float, which flips all
+                // bits of the representation.
+                Self::decode(<$repr as PrimitiveAtomLogic>::fetch_xor(imp, !0, order))
+            }
+        }
+
+        impl From<$float> for $name {
+            fn from(src: $float) -> Self {
+                Self(src)
+            }
+        }
+
+        impl From<$name> for $float {
+            fn from(src: $name) -> Self {
+                src.0
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.0.to_bits() == other.0.to_bits()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> cmp::Ordering {
+                self.0.total_cmp(&other.0)
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.to_bits().hash(state);
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+#[cfg(target_has_atomic = "32")] total_float!(TotalF32, f32, u32);
+#[cfg(target_has_atomic = "64")] total_float!(TotalF64, f64, u64);
@@ -4,7 +4,7 @@
 //! You cannot implement these traits yourself.
 
 use core::{num::Wrapping, sync::atomic::{self, Ordering}};
-use super::{Atom, AtomLogic, AtomInteger};
+use super::{Atom, AtomFloat, AtomLogic, AtomInteger, AtomNiche};
 
 
 // ===============================================================================================
@@ -22,7 +22,7 @@ mod sealed {
 /// atomic type in `std::sync::atomic`. You cannot implement this trait for
 /// your own types; see [`Atom`] instead. This trait's items are not part of
 /// the public API -- see the module docs.
-pub trait PrimitiveAtom: Sized + Copy + sealed::Sealed {
+pub trait PrimitiveAtom: Sized + Copy + PartialEq + sealed::Sealed {
     /// The standard library type that is the atomic version of `Self`.
     type Impl: Send + Sync + Unpin;
 
@@ -75,7 +75,7 @@ pub trait PrimitiveAtom: Sized + Copy + sealed::Sealed {
 /// You cannot implement this trait for your own types; see [`AtomLogic`]
 /// instead. This trait's items are not part of the public API -- see the
 /// module docs.
-pub trait PrimitiveAtomLogic: PrimitiveAtom {
+pub trait PrimitiveAtomLogic: PrimitiveAtomBits {
     #[doc(hidden)]
     fn fetch_and(imp: &Self::Impl, val: Self, order: Ordering) -> Self;
     #[doc(hidden)]
@@ -84,6 +84,9 @@ pub trait PrimitiveAtomLogic: PrimitiveAtom {
     fn fetch_or(imp: &Self::Impl, val: Self, order: Ordering) -> Self;
     #[doc(hidden)]
     fn fetch_xor(imp: &Self::Impl, val: Self, order: Ordering) -> Self;
+    /// The value with all bits set.
+    #[doc(hidden)]
+    const ALL_BITS: Self;
 }
 
 /// Atomic types from `std::sync::atomic` which support integer operations.
@@ -101,6 +104,347 @@ pub trait PrimitiveAtomInteger: PrimitiveAtom {
     fn fetch_max(imp: &Self::Impl, val: Self, order: Ordering) -> Self;
     #[doc(hidden)]
     fn fetch_min(imp: &Self::Impl, val: Self, order: Ordering) -> Self;
+
+    #[doc(hidden)]
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    #[doc(hidden)]
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    #[doc(hidden)]
+    fn saturating_add(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn saturating_sub(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn wrapping_mul(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn wrapping_div(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn wrapping_rem(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn wrapping_neg(self) -> Self;
+    #[doc(hidden)]
+    fn wrapping_shl(self, n: u32) -> Self;
+    #[doc(hidden)]
+    fn wrapping_shr(self, n: u32) -> Self;
+    #[doc(hidden)]
+    fn rotate_left(self, n: u32) -> Self;
+    #[doc(hidden)]
+    fn rotate_right(self, n: u32) -> Self;
+
+    #[doc(hidden)]
+    fn is_zero(self) -> bool;
+    /// Returns `-1` if `self` is negative and `1` otherwise.
+    #[doc(hidden)]
+    fn unit_with_sign_of(self) -> Self;
+}
+
+/// Primitive types whose values are interpreted as the bits of a floating
+/// point number: `u32` as `f32` and `u64` as `f64`.
+///
+/// You cannot implement this trait for your own types; see [`AtomFloat`]
+/// instead. This trait's items are not part of the public API -- see the
+/// module docs.
+pub trait PrimitiveAtomFloat: PrimitiveAtomLogic {
+    /// Only the sign bit is set.
+    #[doc(hidden)]
+    const SIGN_BIT: Self;
+    /// All bits but the sign bit are set.
+    #[doc(hidden)]
+    const MAGNITUDE_BITS: Self;
+
+    #[doc(hidden)]
+    fn float_add(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn float_sub(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn float_mul(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn float_div(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn float_rem(self, rhs: Self) -> Self;
+
+    /// IEEE 754-2019 `maximum`: NaN if any operand is NaN and `-0.0 < +0.0`.
+    #[doc(hidden)]
+    fn float_max(self, rhs: Self) -> Self;
+    /// IEEE 754-2019 `minimum`: NaN if any operand is NaN and `-0.0 < +0.0`.
+    #[doc(hidden)]
+    fn float_min(self, rhs: Self) -> Self;
+}
+
+/// Primitive types that can be packed into a bit pattern together with other
+/// values.
+///
+/// This is used by `derive(Atom)` for structs with multiple fields and is
+/// implemented for `bool` and all integer types. You cannot implement this
+/// trait for your own types. This trait's items are not part of the public
+/// API -- see the module docs.
+pub trait PrimitiveAtomBits: PrimitiveAtom {
+    /// The number of bits needed to represent all values of this type.
+    #[doc(hidden)]
+    const BITS: u32;
+
+    /// Whether this is a signed integer type.
+    #[doc(hidden)]
+    const SIGNED: bool;
+
+    /// Returns the bits of `self`, zero-extended to 64 bits.
+    #[doc(hidden)]
+    fn into_bits(self) -> u64;
+
+    /// Creates a value from the lowest `Self::BITS` bits of `bits`. All other
+    /// bits are ignored.
+    #[doc(hidden)]
+    fn from_bits(bits: u64) -> Self;
+}
+
+/// Returns the lowest `width` bits of `v`. Used by `derive(Atom)` for bitfield
+/// structs.
+///
+/// In debug mode, this panics if `v` cannot be represented with `width` bits,
+/// i.e. if `unpack_bits(pack_bits(v, width), width)` would not return `v`.
+#[doc(hidden)]
+#[inline(always)]
+pub fn pack_bits<T: PrimitiveAtomBits>(v: T, width: u32) -> u64 {
+    let bits = v.into_bits();
+    let packed = bits & mask(width);
+    debug_assert!(
+        unpack_bits::<T>(packed, width).into_bits() == bits,
+        "value does not fit into its bitfield of width {}",
+        width,
+    );
+    packed
+}
+
+/// Creates a value from the lowest `width` bits of `bits`, sign-extending for
+/// signed types. Used by `derive(Atom)` for bitfield structs.
+#[doc(hidden)]
+#[inline(always)]
+pub fn unpack_bits<T: PrimitiveAtomBits>(bits: u64, width: u32) -> T {
+    let mut bits = bits & mask(width);
+    if T::SIGNED && width > 0 && bits >> (width - 1) != 0 {
+        bits |= !mask(width);
+    }
+    T::from_bits(bits)
+}
+
+/// Returns the largest of the given bit widths or 0 if `widths` is empty.
+/// Used by `derive(Atom)` for enums with fields.
+#[doc(hidden)]
+pub const fn max_bits(widths: &[u32]) -> u32 {
+    let mut max = 0;
+    let mut i = 0;
+    while i < widths.len() {
+        if widths[i] > max {
+            max = widths[i];
+        }
+        i += 1;
+    }
+    max
+}
+
+/// Returns `true` if `niche` is never returned by `pack` of an enum with
+/// fields, i.e. if its tag belongs to no variant or to a unit variant (listed
+/// in `unit_tags`) while the payload bits are not zero. Used by
+/// `derive(Atom)`.
+#[doc(hidden)]
+pub const fn niche_unused(
+    niche: u64,
+    payload_bits: u32,
+    num_variants: u64,
+    unit_tags: &[u64],
+) -> bool {
+    let (tag, payload) = match niche.checked_shr(payload_bits) {
+        Some(tag) => (tag, niche ^ (tag << payload_bits)),
+        None => (0, niche),
+    };
+    if tag >= num_variants {
+        return true;
+    }
+
+    let mut i = 0;
+    while i < unit_tags.len() {
+        if unit_tags[i] == tag {
+            return payload != 0;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Returns a `u64` with the lowest `width` bits set. Used by
+/// `derive(AtomLogic)` for structs with multiple fields.
+#[doc(hidden)]
+#[inline(always)]
+pub const fn mask(width: u32) -> u64 {
+    match u64::MAX.checked_shr(64 - width) {
+        Some(mask) => mask,
+        None => 0,
+    }
+}
+
+/// Helper type to select the smallest primitive type with at least `N` bits
+/// via [`SelectRepr`]. Used by `derive(Atom)`.
+#[doc(hidden)]
+pub struct Bits<const N: u32>;
+
+/// Maps `Bits<N>` to the smallest primitive type with at least `N` bits.
+/// There is no impl for `N > 64`. Used by `derive(Atom)`.
+#[doc(hidden)]
+pub trait SelectRepr {
+    type Repr: PrimitiveAtomBits;
+}
+
+/// Selects the [`AtomArithmetic`] impl for [`AtomInteger`] types.
+#[doc(hidden)]
+pub enum IntegerArithmetic {}
+
+/// Selects the [`AtomArithmetic`] impl for [`AtomFloat`] types.
+#[doc(hidden)]
+pub enum FloatArithmetic {}
+
+/// `Atom`s that support [`Atomic::fetch_add`][crate::Atomic::fetch_add],
+/// `fetch_sub`, `fetch_mul`, `fetch_div`, `fetch_rem`, `fetch_neg`,
+/// `fetch_max` and `fetch_min`.
+///
+/// This trait is implemented for all [`AtomInteger`] types (with `K =
+/// IntegerArithmetic`) and all [`AtomFloat`] types (with `K =
+/// FloatArithmetic`). `K` only exists so that both blanket impls can exist
+/// side by side; it is always inferred. You should not implement this trait
+/// yourself, implement one of the marker traits instead. This trait's items
+/// are not part of the public API -- see the module docs.
+pub trait AtomArithmetic<K>: Atom {
+    #[doc(hidden)]
+    fn fetch_add(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self;
+    #[doc(hidden)]
+    fn fetch_sub(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self;
+    #[doc(hidden)]
+    fn fetch_max(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self;
+    #[doc(hidden)]
+    fn fetch_min(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self;
+    #[doc(hidden)]
+    fn fetch_mul(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self;
+    #[doc(hidden)]
+    fn fetch_div(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self;
+    #[doc(hidden)]
+    fn fetch_rem(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self;
+    #[doc(hidden)]
+    fn fetch_neg(imp: &<Self::Repr as PrimitiveAtom>::Impl, order: Ordering) -> Self;
+}
+
+impl<T: AtomInteger> AtomArithmetic<IntegerArithmetic> for T
+where
+    T::Repr: PrimitiveAtomInteger,
+{
+    #[inline(always)]
+    fn fetch_add(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(T::Repr::fetch_add(imp, val.pack(), order))
+    }
+    #[inline(always)]
+    fn fetch_sub(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(T::Repr::fetch_sub(imp, val.pack(), order))
+    }
+    #[inline(always)]
+    fn fetch_max(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(T::Repr::fetch_max(imp, val.pack(), order))
+    }
+    #[inline(always)]
+    fn fetch_min(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(T::Repr::fetch_min(imp, val.pack(), order))
+    }
+    fn fetch_mul(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        let val = val.pack();
+        T::unpack(fetch_op(imp, order, |cur: T::Repr| cur.wrapping_mul(val)))
+    }
+    fn fetch_div(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        let val = val.pack();
+        assert!(!val.is_zero(), "attempt to divide by zero in `Atomic::fetch_div`");
+        T::unpack(fetch_op(imp, order, |cur: T::Repr| cur.wrapping_div(val)))
+    }
+    fn fetch_rem(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        let val = val.pack();
+        assert!(!val.is_zero(), "attempt to calculate the remainder with a divisor of zero \
+            in `Atomic::fetch_rem`");
+        T::unpack(fetch_op(imp, order, |cur: T::Repr| cur.wrapping_rem(val)))
+    }
+    fn fetch_neg(imp: &<Self::Repr as PrimitiveAtom>::Impl, order: Ordering) -> Self {
+        T::unpack(fetch_op(imp, order, T::Repr::wrapping_neg))
+    }
+}
+
+impl<T: AtomFloat> AtomArithmetic<FloatArithmetic> for T
+where
+    T::Repr: PrimitiveAtomFloat,
+{
+    fn fetch_add(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(fetch_float_op(imp, val.pack(), order, T::Repr::float_add))
+    }
+    fn fetch_sub(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(fetch_float_op(imp, val.pack(), order, T::Repr::float_sub))
+    }
+    fn fetch_max(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(fetch_float_op(imp, val.pack(), order, T::Repr::float_max))
+    }
+    fn fetch_min(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(fetch_float_op(imp, val.pack(), order, T::Repr::float_min))
+    }
+    fn fetch_mul(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(fetch_float_op(imp, val.pack(), order, T::Repr::float_mul))
+    }
+    fn fetch_div(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(fetch_float_op(imp, val.pack(), order, T::Repr::float_div))
+    }
+    fn fetch_rem(imp: &<Self::Repr as PrimitiveAtom>::Impl, val: Self, order: Ordering) -> Self {
+        T::unpack(fetch_float_op(imp, val.pack(), order, T::Repr::float_rem))
+    }
+    #[inline(always)]
+    fn fetch_neg(imp: &<Self::Repr as PrimitiveAtom>::Impl, order: Ordering) -> Self {
+        T::unpack(T::Repr::fetch_xor(imp, T::Repr::SIGN_BIT, order))
+    }
+}
+
+/// Replaces the current value `cur` with `op(cur)` in a compare-exchange loop
+/// and returns the previous value. If `op` returns `None`, nothing is stored
+/// and `Err(cur)` is returned.
+pub(crate) fn fetch_checked_op<R: PrimitiveAtom>(
+    imp: &R::Impl,
+    order: Ordering,
+    op: impl FnMut(R) -> Option<R>,
+) -> Result<R, R> {
+    R::fetch_update(imp, order, load_ordering(order), op)
+}
+
+/// Replaces the current value `cur` with `op(cur)` in a compare-exchange loop
+/// and returns the previous value.
+pub(crate) fn fetch_op<R: PrimitiveAtom>(
+    imp: &R::Impl,
+    order: Ordering,
+    mut op: impl FnMut(R) -> R,
+) -> R {
+    match fetch_checked_op(imp, order, |cur| Some(op(cur))) {
+        Ok(prev) | Err(prev) => prev,
+    }
+}
+
+/// Replaces the current value `cur` with `op(cur, val)` in a compare-exchange
+/// loop and returns the previous value. Like the native read-modify-write
+/// operations, this always stores, even if the value does not change, so the
+/// store part of `order` always takes effect.
+pub(crate) fn fetch_float_op<R: PrimitiveAtomFloat>(
+    imp: &R::Impl,
+    val: R,
+    order: Ordering,
+    op: fn(R, R) -> R,
+) -> R {
+    fetch_op(imp, order, |cur| op(cur, val))
+}
+
+/// Returns the strongest ordering valid for the load part of a
+/// read-modify-write operation with the given ordering.
+pub(crate) fn load_ordering(order: Ordering) -> Ordering {
+    match order {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        other => other,
+    }
 }
 
 
@@ -241,6 +585,101 @@ macro_rules! integer_pass_through_methods {
     };
 }
 
+/// Expands to the non-atomic arithmetic methods from `PrimitiveAtomInteger`,
+/// each forwarding to the inherent method of the integer type.
+macro_rules! int_arith_methods {
+    ($ty:ty) => {
+        #[inline(always)]
+        fn checked_add(self, rhs: Self) -> Option<Self> {
+            <$ty>::checked_add(self, rhs)
+        }
+
+        #[inline(always)]
+        fn checked_sub(self, rhs: Self) -> Option<Self> {
+            <$ty>::checked_sub(self, rhs)
+        }
+
+        #[inline(always)]
+        fn saturating_add(self, rhs: Self) -> Self {
+            <$ty>::saturating_add(self, rhs)
+        }
+
+        #[inline(always)]
+        fn saturating_sub(self, rhs: Self) -> Self {
+            <$ty>::saturating_sub(self, rhs)
+        }
+
+        #[inline(always)]
+        fn wrapping_mul(self, rhs: Self) -> Self {
+            <$ty>::wrapping_mul(self, rhs)
+        }
+
+        #[inline(always)]
+        fn wrapping_div(self, rhs: Self) -> Self {
+            <$ty>::wrapping_div(self, rhs)
+        }
+
+        #[inline(always)]
+        fn wrapping_rem(self, rhs: Self) -> Self {
+            <$ty>::wrapping_rem(self, rhs)
+        }
+
+        #[inline(always)]
+        fn wrapping_neg(self) -> Self {
+            <$ty>::wrapping_neg(self)
+        }
+
+        #[inline(always)]
+        fn wrapping_shl(self, n: u32) -> Self {
+            <$ty>::wrapping_shl(self, n)
+        }
+
+        #[inline(always)]
+        fn wrapping_shr(self, n: u32) -> Self {
+            <$ty>::wrapping_shr(self, n)
+        }
+
+        #[inline(always)]
+        fn rotate_left(self, n: u32) -> Self {
+            <$ty>::rotate_left(self, n)
+        }
+
+        #[inline(always)]
+        fn rotate_right(self, n: u32) -> Self {
+            <$ty>::rotate_right(self, n)
+        }
+
+        #[inline(always)]
+        fn is_zero(self) -> bool {
+            self == 0
+        }
+
+        #[inline(always)]
+        fn unit_with_sign_of(self) -> Self {
+            // For signed types, `!0` is `-1`.
+            if <$ty>::MIN != 0 && self.leading_zeros() == 0 { !0 } else { 1 }
+        }
+    };
+}
+
+/// Expands to all methods from `PrimitiveAtomBits` for integer types.
+macro_rules! int_bits_methods {
+    ($ty:ty, $unsigned:ty) => {
+        const BITS: u32 = <$ty>::BITS;
+        const SIGNED: bool = <$ty>::MIN != 0;
+
+        #[inline(always)]
+        fn into_bits(self) -> u64 {
+            self as $unsigned as u64
+        }
+
+        #[inline(always)]
+        fn from_bits(bits: u64) -> Self {
+            bits as $ty
+        }
+    };
+}
+
 // ----- `*mut T` and `AtomicPtr` -----
 #[cfg(target_has_atomic = "ptr")]
 impl<T> Atom for *mut T {
@@ -262,7 +701,7 @@ impl<T> PrimitiveAtom for *mut T {
 // ----- Integers and `bool` -----
 
 macro_rules! impl_std_atomics {
-    ($ty:ty, $non_zero_ty:ident, $impl_ty:ident, $is_int:ident) => {
+    ($ty:ty, $unsigned:ty, $non_zero_ty:ident, $impl_ty:ident, $is_int:ident) => {
         impl Atom for $ty {
             type Repr = Self;
             id_pack_unpack!();
@@ -276,15 +715,22 @@ macro_rules! impl_std_atomics {
         }
 
         impl PrimitiveAtomLogic for $ty {
+            const ALL_BITS: Self = impl_std_atomics!(@all_bits $is_int);
             logical_pass_through_methods!();
         }
 
-        impl_std_atomics!(@int_methods $ty, $non_zero_ty, $impl_ty, $is_int);
+        impl_std_atomics!(@int_methods $ty, $unsigned, $non_zero_ty, $impl_ty, $is_int);
     };
-    (@int_methods $ty:ty, $non_zero_ty:ident, $impl_ty:ident, true) => {
+    (@all_bits true) => { !0 };
+    (@all_bits false) => { true };
+    (@int_methods $ty:ty, $unsigned:ty, $non_zero_ty:ident, $impl_ty:ident, true) => {
         impl AtomInteger for $ty {}
         impl PrimitiveAtomInteger for $ty {
             integer_pass_through_methods!();
+            int_arith_methods!($ty);
+        }
+        impl PrimitiveAtomBits for $ty {
+            int_bits_methods!($ty, $unsigned);
         }
 
         impl Atom for core::num::$non_zero_ty {
@@ -299,22 +745,56 @@ macro_rules! impl_std_atomics {
                 // atomic operation. Thus this should never happen.
                 Self::new(src).expect("zero value in `Atom::unpack` for NonZero type")
             }
+
+            fn try_unpack(src: Self::Repr) -> Option<Self> {
+                Self::new(src)
+            }
         }
     };
-    (@int_methods $ty:ty, $non_zero_ty:ident, $impl_ty:ident, false) => {};
+    (@int_methods $ty:ty, $unsigned:ty, $non_zero_ty:ident, $impl_ty:ident, false) => {};
+}
+
+#[cfg(target_has_atomic = "8")] impl_std_atomics!(bool, bool, _Dummy, AtomicBool, false);
+#[cfg(target_has_atomic = "8")] impl_std_atomics!(u8, u8, NonZeroU8, AtomicU8, true);
+#[cfg(target_has_atomic = "8")] impl_std_atomics!(i8, u8, NonZeroI8, AtomicI8, true);
+#[cfg(target_has_atomic = "16")] impl_std_atomics!(u16, u16, NonZeroU16, AtomicU16, true);
+#[cfg(target_has_atomic = "16")] impl_std_atomics!(i16, u16, NonZeroI16, AtomicI16, true);
+#[cfg(target_has_atomic = "32")] impl_std_atomics!(u32, u32, NonZeroU32, AtomicU32, true);
+#[cfg(target_has_atomic = "32")] impl_std_atomics!(i32, u32, NonZeroI32, AtomicI32, true);
+#[cfg(target_has_atomic = "64")] impl_std_atomics!(u64, u64, NonZeroU64, AtomicU64, true);
+#[cfg(target_has_atomic = "64")] impl_std_atomics!(i64, u64, NonZeroI64, AtomicI64, true);
+#[cfg(target_has_atomic = "ptr")] impl_std_atomics!(usize, usize, NonZeroUsize, AtomicUsize, true);
+#[cfg(target_has_atomic = "ptr")] impl_std_atomics!(isize, usize, NonZeroIsize, AtomicIsize, true);
+
+#[cfg(target_has_atomic = "8")]
+impl PrimitiveAtomBits for bool {
+    const BITS: u32 = 1;
+    const SIGNED: bool = false;
+
+    #[inline(always)]
+    fn into_bits(self) -> u64 {
+        self as u64
+    }
+
+    #[inline(always)]
+    fn from_bits(bits: u64) -> Self {
+        bits & 1 != 0
+    }
+}
+
+macro_rules! impl_select_repr {
+    ($repr:ty: $($n:literal)*) => {
+        $( impl SelectRepr for Bits<$n> { type Repr = $repr; } )*
+    };
 }
 
-#[cfg(target_has_atomic = "8")] impl_std_atomics!(bool, _Dummy, AtomicBool, false);
-#[cfg(target_has_atomic = "8")] impl_std_atomics!(u8, NonZeroU8, AtomicU8, true);
-#[cfg(target_has_atomic = "8")] impl_std_atomics!(i8, NonZeroI8, AtomicI8, true);
-#[cfg(target_has_atomic = "16")] impl_std_atomics!(u16, NonZeroU16, AtomicU16, true);
-#[cfg(target_has_atomic = "16")] impl_std_atomics!(i16, NonZeroI16, AtomicI16, true);
-#[cfg(target_has_atomic = "32")] impl_std_atomics!(u32, NonZeroU32, AtomicU32, true);
-#[cfg(target_has_atomic = "32")] impl_std_atomics!(i32, NonZeroI32, AtomicI32, true);
-#[cfg(target_has_atomic = "64")] impl_std_atomics!(u64, NonZeroU64, AtomicU64, true);
-#[cfg(target_has_atomic = "64")] impl_std_atomics!(i64, NonZeroI64, AtomicI64, true);
-#[cfg(target_has_atomic = "ptr")] impl_std_atomics!(usize, NonZeroUsize, AtomicUsize, true);
-#[cfg(target_has_atomic = "ptr")] impl_std_atomics!(isize, NonZeroIsize, AtomicIsize, true);
+#[cfg(target_has_atomic = "8")] impl_select_repr!(u8: 0 1 2 3 4 5 6 7 8);
+#[cfg(target_has_atomic = "16")] impl_select_repr!(u16: 9 10 11 12 13 14 15 16);
+#[cfg(target_has_atomic = "32")] impl_select_repr!(u32:
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+#[cfg(target_has_atomic = "64")] impl_select_repr!(u64:
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48
+    49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64);
 
 // ----- Implementations for non-atomic primitive types ------------------------------------------
 #[cfg(target_has_atomic = "32")]
@@ -339,6 +819,61 @@ impl Atom for f64 {
     }
 }
 
+macro_rules! impl_primitive_atom_float {
+    ($repr:ty, $float:ty) => {
+        impl AtomFloat for $float {}
+        impl PrimitiveAtomFloat for $repr {
+            const SIGN_BIT: Self = 1 << (<$repr>::BITS - 1);
+            const MAGNITUDE_BITS: Self = !Self::SIGN_BIT;
+
+            #[inline(always)]
+            fn float_add(self, rhs: Self) -> Self {
+                (<$float>::from_bits(self) + <$float>::from_bits(rhs)).to_bits()
+            }
+            #[inline(always)]
+            fn float_sub(self, rhs: Self) -> Self {
+                (<$float>::from_bits(self) - <$float>::from_bits(rhs)).to_bits()
+            }
+            #[inline(always)]
+            fn float_mul(self, rhs: Self) -> Self {
+                (<$float>::from_bits(self) * <$float>::from_bits(rhs)).to_bits()
+            }
+            #[inline(always)]
+            fn float_div(self, rhs: Self) -> Self {
+                (<$float>::from_bits(self) / <$float>::from_bits(rhs)).to_bits()
+            }
+            #[inline(always)]
+            fn float_rem(self, rhs: Self) -> Self {
+                (<$float>::from_bits(self) % <$float>::from_bits(rhs)).to_bits()
+            }
+
+            fn float_max(self, rhs: Self) -> Self {
+                let (a, b) = (<$float>::from_bits(self), <$float>::from_bits(rhs));
+                if a.is_nan() || b.is_nan() {
+                    (a + b).to_bits()
+                } else if a > b || (a == b && a.is_sign_positive()) {
+                    self
+                } else {
+                    rhs
+                }
+            }
+            fn float_min(self, rhs: Self) -> Self {
+                let (a, b) = (<$float>::from_bits(self), <$float>::from_bits(rhs));
+                if a.is_nan() || b.is_nan() {
+                    (a + b).to_bits()
+                } else if a < b || (a == b && a.is_sign_negative()) {
+                    self
+                } else {
+                    rhs
+                }
+            }
+        }
+    };
+}
+
+#[cfg(target_has_atomic = "32")] impl_primitive_atom_float!(u32, f32);
+#[cfg(target_has_atomic = "64")] impl_primitive_atom_float!(u64, f64);
+
 #[cfg(target_has_atomic = "32")]
 impl Atom for char {
     type Repr = u32;
@@ -348,6 +883,9 @@ impl Atom for char {
     fn unpack(src: Self::Repr) -> Self {
         Self::try_from(src).expect("invalid value in <char as Atom>::unpack")
     }
+    fn try_unpack(src: Self::Repr) -> Option<Self> {
+        Self::try_from(src).ok()
+    }
 }
 
 // We do not implement `AtomInteger` as, to me, it seems like the exact adding
@@ -360,8 +898,16 @@ impl<T: Atom> Atom for Wrapping<T> {
     fn unpack(src: Self::Repr) -> Self {
         Self(T::unpack(src))
     }
+    fn try_unpack(src: Self::Repr) -> Option<Self> {
+        T::try_unpack(src).map(Self)
+    }
+}
+impl<T: AtomLogic> AtomLogic for Wrapping<T>
+where
+    T::Repr: PrimitiveAtomLogic,
+{
+    const NOT_MASK: Self::Repr = T::NOT_MASK;
 }
-impl<T: AtomLogic> AtomLogic for Wrapping<T> where T::Repr: PrimitiveAtomLogic {}
 
 
 #[cfg(target_has_atomic = "ptr")]
@@ -374,42 +920,54 @@ impl<T> Atom for core::ptr::NonNull<T> {
         Self::new(<*mut T>::unpack(src))
             .expect("null value in `<NonNull<T> as Atom>::unpack`")
     }
+    fn try_unpack(src: Self::Repr) -> Option<Self> {
+        Self::new(<*mut T>::unpack(src))
+    }
 }
 
 #[cfg(target_has_atomic = "ptr")]
-impl<T> Atom for Option<core::ptr::NonNull<T>> {
-    type Repr = *mut T;
+impl<T> AtomNiche for core::ptr::NonNull<T> {
+    const NICHE: Self::Repr = core::ptr::null_mut();
+}
+
+impl<T: AtomNiche> Atom for Option<T> {
+    type Repr = T::Repr;
     fn pack(self) -> Self::Repr {
-        self.map(|nn| nn.as_ptr())
-            .unwrap_or(core::ptr::null_mut())
-            .pack()
+        match self {
+            Some(v) => v.pack(),
+            None => T::NICHE,
+        }
     }
     fn unpack(src: Self::Repr) -> Self {
-        if src.is_null() {
+        if src == T::NICHE {
             None
         } else {
-            Some(core::ptr::NonNull::new(<*mut T>::unpack(src)).unwrap())
+            Some(T::unpack(src))
+        }
+    }
+    fn try_unpack(src: Self::Repr) -> Option<Self> {
+        if src == T::NICHE {
+            Some(None)
+        } else {
+            T::try_unpack(src).map(Some)
         }
     }
 }
 
 macro_rules! impl_option_non_zero {
     ($ty:ident = $repr:ty) => {
-        impl Atom for Option<core::num::$ty> {
-            type Repr = $repr;
-            fn pack(self) -> Self::Repr {
-                self.map(core::num::$ty::get).unwrap_or(0).pack()
-            }
-            fn unpack(src: Self::Repr) -> Self {
-                core::num::$ty::new(src)
-            }
+        impl AtomNiche for core::num::$ty {
+            const NICHE: Self::Repr = 0;
         }
 
         // Semantically, an `Option<NonZeroFoo>` represents `Foo` exactly. It
         // also has the exact same memory layout. It's just that we assign
         // the "symbol" `None` to 0. Any integer operation that leads to 0 on
-        // the underlying type will result in `None`.
-        impl AtomInteger for Option<core::num::$ty> {}
+        // the underlying type will result in `None`, except for checked and
+        // saturating ones.
+        impl AtomInteger for Option<core::num::$ty> {
+            const NON_ZERO: bool = true;
+        }
     };
 }
 
@@ -479,9 +1037,16 @@ macro_rules! impl_small_primitive_array {
 /// This is just a dummy module to have doc tests.
 ///
 /// ```
-/// use atomig::{Atom, AtomLogic, AtomInteger, impls::{PrimitiveAtomLogic, PrimitiveAtomInteger}};
+/// use atomig::{
+///     Atom, AtomFloat, AtomLogic, AtomInteger,
+///     impls::{PrimitiveAtomFloat, PrimitiveAtomLogic, PrimitiveAtomInteger},
+/// };
 ///
 /// fn assert_impl_atom<T: Atom>() {}
+/// fn assert_impl_atom_float<T: AtomFloat>()
+/// where
+///     T::Repr: PrimitiveAtomFloat,
+/// {}
 /// fn assert_impl_atom_logic<T: AtomLogic>()
 /// where
 ///     T::Repr: PrimitiveAtomLogic,
@@ -512,8 +1077,8 @@ macro_rules! impl_small_primitive_array {
 /// assert_impl_atom::<Option<core::ptr::NonNull<String>>>();
 ///
 /// assert_impl_atom::<char>();
-/// assert_impl_atom::<f32>();
-/// assert_impl_atom::<f64>();
+/// assert_impl_atom_float::<f32>();
+/// assert_impl_atom_float::<f64>();
 ///
 /// assert_impl_atom::<[u8; 2]>();
 /// assert_impl_atom::<[u8; 4]>();
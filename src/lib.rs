@@ -50,6 +50,11 @@
 //! `#[derive(Atom)]` for your own types. See [`Atom`]'s documentation for more
 //! information.
 //!
+//! The marker traits [`AtomLogic`], [`AtomInteger`] and [`AtomFloat`] enable
+//! logical, integer and floating point operations on `Atomic<T>`,
+//! respectively. Types implementing [`AtomNiche`] can be used as
+//! `Atomic<Option<T>>` without a wider representation.
+//!
 //!
 //! # Notes
 //!
@@ -65,11 +70,12 @@
 //! # Cargo features
 //!
 //! This crate has some Cargo features which are disabled by default:
-//! - **`derive`**: enables the custom derives for [`Atom`], [`AtomLogic`] and
-//!   [`AtomInteger`]. It is disabled by default because it requires compiling
-//!   a few dependencies for procedural macros.
+//! - **`derive`**: enables the custom derives for [`Atom`], [`AtomLogic`],
+//!   [`AtomInteger`], [`AtomFloat`] and [`AtomFlags`]. It is disabled by
+//!   default because it requires compiling a few dependencies for procedural
+//!   macros.
 //! - **`serde`**: enables the serde `Serialize` and `Deserialize` traits on
-//!     `Atomic<T>` if `T` is serializable or deserializable.
+//!   `Atomic<T>` if `T` is serializable or deserializable.
 //!
 
 #![forbid(unsafe_code)]
@@ -79,10 +85,19 @@
 #[macro_use]
 extern crate std;
 
+// Allows using the custom derives in this crate's own tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as atomig;
+
 use core::fmt;
-use crate::impls::{PrimitiveAtom, PrimitiveAtomLogic, PrimitiveAtomInteger};
+use crate::impls::{
+    AtomArithmetic, PrimitiveAtom, PrimitiveAtomBits, PrimitiveAtomFloat, PrimitiveAtomInteger,
+    PrimitiveAtomLogic,
+};
 
+mod float;
 pub mod impls;
+mod macros;
 #[cfg(test)]
 mod tests;
 
@@ -90,8 +105,13 @@ mod tests;
 #[doc(no_inline)]
 pub use core::sync::atomic::Ordering;
 
+#[cfg(target_has_atomic = "32")]
+pub use crate::float::TotalF32;
+#[cfg(target_has_atomic = "64")]
+pub use crate::float::TotalF64;
+
 #[cfg(feature = "derive")]
-pub use atomig_macro::{Atom, AtomInteger, AtomLogic};
+pub use atomig_macro::{Atom, AtomFlags, AtomFloat, AtomInteger, AtomLogic};
 
 // ===============================================================================================
 // ===== User faced `Atom*` traits
@@ -143,7 +163,9 @@ pub use atomig_macro::{Atom, AtomInteger, AtomLogic};
 ///
 /// Instead of implementing the trait manually (like shown above), you can
 /// derive it automatically in many cases. In order to use that feature, you
-/// have to enabled the Cargo feature 'derive'.
+/// have to enabled the Cargo feature 'derive'. Without it, C-like enums and
+/// newtypes can still be defined via the [`atom_enum!`] and [`atom_newtype!`]
+/// macros.
 ///
 /// ```
 /// use atomig::{Atom, Atomic, Ordering};
@@ -161,12 +183,35 @@ pub use atomig_macro::{Atom, AtomInteger, AtomLogic};
 /// # fn main() {}
 /// ```
 ///
-/// The trait can be automatically derived for two kinds of types:
+/// The trait can be automatically derived for four kinds of types:
 /// - `struct` types with only *one* field. That field's type has to implement
 ///   `Atom`. Works with tuple structs or normal structs with one named field.
-/// - `enum` types that have a `#[repr(_)]` attribute specified and are C-like
-///   (i.e. no variant has any fields). The primitive type specified in the
-///   `#[repr(_)]` attribute is used as `Repr` type.
+///   Generic parameters are supported: if the field's type mentions one, an
+///   `Atom` bound for it is added to the impl. Phantom fields (`PhantomData<_>`,
+///   `PhantomPinned` and `()`) are not counted as fields and are initialized
+///   with their default value in `unpack`, so typed IDs like
+///   `struct Id<T>(u32, PhantomData<T>)` work. The field's `Repr` and
+///   `unpack` are used, so `#[atom(repr = _)]` and `#[atom(fallback = _)]`
+///   are rejected.
+/// - `struct` types with multiple fields, see below.
+/// - `enum` types that are C-like (i.e. no variant has any fields). The
+///   primitive integer type specified in the `#[repr(_)]` attribute is used as
+///   `Repr` type. Other hints in that attribute (e.g. `align(_)`) are ignored.
+///   Without such an attribute, the smallest integer type that fits all
+///   discriminant values is used, which requires all explicit discriminants
+///   to be integer literals. You can also specify the `Repr` type explicitly
+///   via `#[atom(repr = u32)]`. The derived `unpack` runs in constant time,
+///   regardless of the number of variants.
+/// - `enum` types where every variant has at most one field, see below.
+/// - Any type with `#[atom(repr = _, with = _)]` or `#[atom(repr = _, via = _)]`,
+///   see below.
+///
+/// ```compile_fail
+/// # use atomig::Atom;
+/// #[derive(Atom)]
+/// #[atom(repr = u32)]  // error: the `Repr` of the field (`u16`) is used
+/// struct Port(u16);
+/// ```
 ///
 /// Example with enum:
 ///
@@ -181,11 +226,243 @@ pub use atomig_macro::{Atom, AtomInteger, AtomLogic};
 ///
 /// let a = Atomic::new(Animal::Cat);
 /// a.store(Animal::Fox, Ordering::SeqCst);
+///
+/// // `Repr` is inferred as `u16`
+/// #[derive(Atom)]
+/// enum Status { Ok = 200, NotFound = 404 }
+/// # }
+///
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+///
+/// By default, the derived `unpack` for enums panics if the value does not
+/// correspond to any variant. Instead, you can specify a fallback: either a
+/// variant via `#[atom(fallback = Unknown)]` on the enum or `#[atom(fallback)]`
+/// on the variant, or a function `fn(Repr) -> Self` via
+/// `#[atom(fallback = "path::to::function")]`. All invalid values are then
+/// mapped to that variant or passed to that function.
+///
+/// ```
+/// use atomig::Atom;
+/// # #[cfg(feature = "derive")]
+/// # fn main() {
+///
+/// #[derive(Debug, PartialEq, Atom)]
+/// #[repr(u8)]
+/// #[atom(fallback = Unknown)]
+/// enum ConnState { Idle, Open, Closed, Unknown }
+///
+/// assert_eq!(ConnState::unpack(27), ConnState::Unknown);
+/// # }
+///
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+///
+/// Instead of a fallback, an enum can have one variant marked with
+/// `#[atom(other)]` that holds a single primitive integer field, which is
+/// then used as `Repr` type. All other variants have to be unit variants and
+/// their explicit discriminants have to be integer literals. All values that
+/// are not a discriminant of another variant are unpacked into that variant,
+/// so nothing is lost and `unpack` never panics. Note that, e.g., `Other(0)`
+/// is unpacked as the variant with discriminant 0, if there is one.
+///
+/// ```
+/// use atomig::Atom;
+/// # #[cfg(feature = "derive")]
+/// # fn main() {
+///
+/// #[derive(Debug, PartialEq, Atom)]
+/// #[repr(u8)]
+/// enum Status {
+///     Ok = 0,
+///     NotFound = 4,
+///     #[atom(other)]
+///     Other(u8),
+/// }
+///
+/// assert_eq!(Status::unpack(4), Status::NotFound);
+/// assert_eq!(Status::unpack(27), Status::Other(27));
+/// assert_eq!(Status::Other(27).pack(), 27);
+/// # }
+///
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+///
+/// Enums with fields are stored as a tag (the index of the variant) in the
+/// high bits and the atomic representation of the field in the low bits. The
+/// same restrictions as for fields of packed structs apply. The smallest
+/// unsigned integer fitting tag and largest field is used as `Repr`; if none
+/// fits, compilation fails. A fallback variant must not have fields.
+///
+/// ```
+/// use atomig::{Atom, Atomic, Ordering};
+/// # #[cfg(feature = "derive")]
+/// # fn main() {
+///
+/// #[derive(Debug, PartialEq, Atom)]
+/// enum ErrorCode { Timeout, Refused }
+///
+/// #[derive(Debug, PartialEq, Atom)]
+/// enum Task { Idle, Running(u32), Failed(ErrorCode) }  // Repr = u64
+///
+/// let a = Atomic::new(Task::Idle);
+/// a.store(Task::Running(7), Ordering::SeqCst);
+/// assert_eq!(a.load(Ordering::SeqCst), Task::Running(7));
+/// # }
+///
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+///
+/// For structs with multiple fields, the atomic representations of all fields
+/// are packed into one integer by shifting and masking. The smallest unsigned
+/// integer that can hold all fields is used as `Repr` type; if they need more
+/// than 64 bits, compilation fails. Each field's `Repr` has to be `bool` or an
+/// integer type (pointers are not supported) and the struct cannot have
+/// generic parameters. `bool` fields only take up a single bit.
+///
+/// Additionally, a trait `Atomic{Name}Fields` is generated and implemented for
+/// `Atomic<Name>`. It has the methods `load_{field}` and
+/// `fetch_update_{field}` for every field that is at least as visible as the
+/// struct.
+///
+/// ```
+/// use atomig::{Atom, Atomic, Ordering};
+/// # #[cfg(feature = "derive")]
+/// # fn main() {
+///
+/// #[derive(Debug, PartialEq, Atom)]
+/// struct Slot { gen: u16, idx: u16, state: u8 }  // Repr = u64
+///
+/// // The generated trait `AtomicSlotFields` has to be in scope.
+/// let a = Atomic::new(Slot { gen: 0, idx: 3, state: 1 });
+/// assert_eq!(a.load_idx(Ordering::SeqCst), 3);
+/// let prev = a.fetch_update_gen(Ordering::SeqCst, Ordering::SeqCst, |g| Some(g + 1));
+/// assert_eq!(prev, Ok(0));
+/// assert_eq!(a.load(Ordering::SeqCst), Slot { gen: 1, idx: 3, state: 1 });
 /// # }
 ///
 /// # #[cfg(not(feature = "derive"))]
 /// # fn main() {}
 /// ```
+///
+/// Options that do not apply to the layout are rejected instead of ignored:
+/// `repr` cannot be chosen for packed structs, `fallback` only applies to
+/// enums and conversions, and `bitfield` only applies to structs.
+///
+/// ```compile_fail
+/// # use atomig::Atom;
+/// #[derive(Atom)]
+/// #[atom(repr = u64)]  // error: `Repr` is chosen automatically
+/// struct Slot { gen: u16, idx: u16 }
+/// ```
+///
+/// With `#[atom(bitfield)]` on the struct, every field gets an explicit width
+/// via `#[atom(bits = N)]` instead. `bool` fields default to one bit. Fields
+/// are packed in declaration order, starting at the least significant bit.
+/// The smallest unsigned integer fitting all fields is used as `Repr`, unless
+/// you specify one via `#[atom(bitfield, repr = u32)]`. Layouts that do not
+/// fit into `Repr` are rejected at compile time. Signed integers are
+/// sign-extended when unpacking. In debug mode, packing a value that does not
+/// fit into its bitfield panics.
+///
+/// ```
+/// use atomig::{Atom, Atomic, Ordering};
+/// # #[cfg(feature = "derive")]
+/// # fn main() {
+///
+/// #[derive(Atom)]
+/// #[repr(u8)]
+/// enum Mode { Idle, Read, Write }
+///
+/// #[derive(Atom)]
+/// #[atom(bitfield)]
+/// struct LockWord {
+///     locked: bool,
+///     #[atom(bits = 2)]
+///     mode: Mode,
+///     #[atom(bits = 13)]
+///     readers: u16,
+/// }
+///
+/// let a = Atomic::new(LockWord { locked: false, mode: Mode::Idle, readers: 0 });
+/// assert_eq!(a.load(Ordering::SeqCst).pack(), 0u16);
+/// # }
+///
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// # use atomig::Atom;
+/// #[derive(Atom)]
+/// #[atom(bitfield, repr = u8)]
+/// struct TooLarge {
+///     #[atom(bits = 6)]
+///     a: u8,
+///     #[atom(bits = 3)]
+///     b: u8,  // error: does not fit into `u8` anymore
+/// }
+/// ```
+///
+/// Finally, the conversion can be taken from existing code, which works for
+/// all kinds of types. Both variants require specifying `Repr` via
+/// `#[atom(repr = _)]`:
+/// - `#[atom(with = "path::to::module")]`: `pack` and `unpack` simply call
+///   the functions `pack(Self) -> Repr` and `unpack(Repr) -> Self` of the
+///   given module.
+/// - `#[atom(via = "into")]` uses `Into<Repr>` for `pack` and `From<Repr>` for
+///   `unpack`. `#[atom(via = "try_from")]` uses `TryFrom<Repr>` for `unpack`
+///   instead, which panics on error unless a fallback is specified (as for
+///   enums above: a variant, associated constant or function).
+///
+/// ```
+/// use atomig::Atom;
+/// # #[cfg(feature = "derive")]
+/// # fn main() {
+///
+/// #[derive(Debug, PartialEq, Atom)]
+/// #[atom(repr = u8, via = "try_from", fallback = Nop)]
+/// enum Opcode { Nop, Push, Pop }
+///
+/// impl From<Opcode> for u8 {
+///     fn from(op: Opcode) -> u8 {
+///         op as u8
+///     }
+/// }
+///
+/// impl TryFrom<u8> for Opcode {
+///     type Error = ();
+///     fn try_from(src: u8) -> Result<Self, ()> {
+///         match src {
+///             0 => Ok(Opcode::Nop),
+///             1 => Ok(Opcode::Push),
+///             2 => Ok(Opcode::Pop),
+///             _ => Err(()),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(Opcode::unpack(1), Opcode::Push);
+/// assert_eq!(Opcode::unpack(9), Opcode::Nop);
+/// # }
+///
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+///
+/// With `#[atom(niche = _)]`, the derive additionally implements
+/// [`AtomNiche`], which makes `Option<Self>` usable in `Atomic` with the same
+/// representation.
+///
+/// The generated code refers to this crate by the name it has in your
+/// `Cargo.toml`, so renaming the dependency works. If you use `atomig` through
+/// a re-export in another crate, specify the path to it via
+/// `#[atom(crate = "my_facade::atomig")]`. This attribute is supported by all
+/// derives of this crate.
 pub trait Atom {
     /// The atomic representation of this type.
     ///
@@ -208,6 +485,21 @@ pub trait Atom {
     /// that invalid input values do not lead to undefined behavior (e.g. memory
     /// unsafety)!
     fn unpack(src: Self::Repr) -> Self;
+
+    /// Like `unpack`, but returns `None` if `src` is not a valid
+    /// representation of this type instead of panicking (or returning a
+    /// fallback value).
+    ///
+    /// This is used by [`Atomic::try_load`] and similar methods. The default
+    /// implementation simply calls `unpack`, which is correct for all types
+    /// for which every value of `Repr` is valid. Types whose `unpack` can
+    /// panic should override this method.
+    fn try_unpack(src: Self::Repr) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        Some(Self::unpack(src))
+    }
 }
 
 /// `Atom`s for which logical operations on their atomic representation make
@@ -240,6 +532,28 @@ pub trait Atom {
 /// feature of this crate is enabled. This custom derive is simpler because
 /// this is only a marker trait.
 ///
+/// For structs with multiple fields (including bitfield structs), the derive
+/// checks that all field types implement `AtomLogic` as well, since the
+/// logical operations are applied to all fields at once. The fields also have
+/// to fill the whole representation: operations like `fetch_not` would
+/// otherwise set the unused bits, after which `compare_exchange` with a
+/// loaded value never succeeds.
+///
+/// ```compile_fail
+/// # use atomig::{Atom, AtomLogic};
+/// #[derive(Atom, AtomLogic)]
+/// struct Pair {
+///     a: u8,
+///     b: bool,  // error: the 9 bits do not fill the `u16` representation
+/// }
+/// ```
+///
+/// For structs with multiple fields, `NOT_MASK` is made up of the `NOT_MASK`
+/// of each field, so `fetch_not` only flips the bits each field allows.
+/// Structs with a single field take over the field's `NOT_MASK`. For generic
+/// structs with one field whose type mentions a type parameter, bounds
+/// requiring that type to implement `AtomLogic` are added to the impl.
+///
 /// *However*, this trait cannot be derived for enums, as this is almost
 /// certainly incorrect. While in C, enums basically list some constants and
 /// often, these constants are used in bitwise logical operations, this is
@@ -251,7 +565,13 @@ pub trait Atom {
 pub trait AtomLogic: Atom
 where
     Self::Repr: PrimitiveAtomLogic,
-{}
+{
+    /// The bits of the representation that [`Atomic::fetch_not`] flips.
+    /// Defaults to all bits. Types that do not use all bits of their
+    /// representation, like the sets generated by `derive(AtomFlags)`, set
+    /// this to the used bits so that `fetch_not` does not set the others.
+    const NOT_MASK: Self::Repr = <Self::Repr as PrimitiveAtomLogic>::ALL_BITS;
+}
 
 /// `Atom`s for which integer operations on their atomic representation make
 /// sense.
@@ -280,6 +600,14 @@ where
 /// feature of this crate is enabled. This custom derive is simpler because
 /// this is only a marker trait.
 ///
+/// This derive is not available for structs with multiple fields (including
+/// bitfield structs), as the integer operations would carry over from one
+/// field into the next.
+///
+/// For generic structs with one field whose type mentions a type parameter,
+/// bounds requiring that type to implement `AtomInteger` are added to the
+/// impl.
+///
 /// *However*, this trait cannot be derived for enums, as this is almost
 /// certainly incorrect. While in C, enums basically list some constants and
 /// often, these constants are added or subtracted from one another, this is
@@ -291,8 +619,176 @@ where
 pub trait AtomInteger: Atom
 where
     Self::Repr: PrimitiveAtomInteger,
+{
+    /// Whether the representation zero is not an integer value of this type,
+    /// like `None` for `Option<NonZeroU32>`. If `true`, the checked and
+    /// saturating operations (e.g. [`Atomic::checked_fetch_add`]) never turn
+    /// a non-zero value into zero. Defaults to `false`. `derive(AtomInteger)`
+    /// and `atom_newtype!` take the value of the field's type.
+    const NON_ZERO: bool = false;
+}
+
+/// `Atom`s for which floating point operations on their atomic representation
+/// make sense.
+///
+/// The representation has to be `u32` or `u64`, which is interpreted as the
+/// bits of an `f32` or `f64`, respectively. This trait is implemented for
+/// `f32` and `f64` and makes it possible to use [`Atomic::fetch_add`],
+/// [`Atomic::fetch_sub`], [`Atomic::fetch_mul`], [`Atomic::fetch_div`],
+/// [`Atomic::fetch_rem`], [`Atomic::fetch_neg`], [`Atomic::fetch_max`] and
+/// [`Atomic::fetch_min`] with floating point semantics, as well as
+/// [`Atomic::fetch_abs`]. `fetch_neg` and `fetch_abs` are a single atomic
+/// logical operation on the sign bit, all other operations are
+/// compare-exchange loops which, like the native operations, always store.
+///
+/// `fetch_max` and `fetch_min` follow the `maximum` and `minimum` operations
+/// of IEEE 754-2019: if either value is NaN, the result is NaN, and `-0.0` is
+/// considered smaller than `+0.0`. If you mostly need `fetch_max` or
+/// `fetch_min`, consider [`TotalF32`] and [`TotalF64`] instead: their
+/// representation allows these to be single atomic operations.
+///
+/// A type must not implement both `AtomFloat` and [`AtomInteger`], as it would
+/// be ambiguous which `fetch_add` to use.
+///
+/// ```
+/// use atomig::{Atomic, Ordering};
+///
+/// let sum = Atomic::new(1.5f64);
+/// sum.fetch_add(2.0, Ordering::SeqCst);
+/// sum.fetch_mul(3.0, Ordering::SeqCst);
+/// assert_eq!(sum.fetch_neg(Ordering::SeqCst), 10.5);
+/// assert_eq!(sum.load(Ordering::SeqCst), -10.5);
+/// ```
+///
+///
+/// # Deriving this trait
+///
+/// Like [`Atom`], this trait can automatically derived if the 'derive' Cargo
+/// feature of this crate is enabled. This is useful for newtypes around `f32`
+/// or `f64`, e.g. `struct Seconds(f64)`. Like for [`AtomInteger`], this derive
+/// is not available for enums and structs with multiple fields.
+pub trait AtomFloat: Atom
+where
+    Self::Repr: PrimitiveAtomFloat,
 {}
 
+/// Flags that can be stored in an atomic set of flags.
+///
+/// This trait is meant to be derived for C-like enums whose variants are
+/// single bits (i.e. all discriminants are distinct powers of two). While
+/// `AtomLogic` cannot be derived for such an enum itself (a combination of
+/// flags is not a valid value of the enum), the derive generates a companion
+/// set type `{Name}Set` that implements [`Atom`] and [`AtomLogic`]. It has
+/// the same visibility as the enum and offers `insert`, `remove`, `contains`,
+/// `is_empty`, `iter`, the constants `EMPTY` and `ALL` and the operators `|`,
+/// `&` and `!`. `Atomic<{Name}Set>` can then be used with
+/// [`Atomic::fetch_insert`], [`Atomic::fetch_remove`] and
+/// [`Atomic::contains`].
+///
+/// The `Repr` type of the set is chosen like for `derive(Atom)` on C-like
+/// enums: via `#[repr(_)]` or `#[atom(repr = _)]`, or the smallest integer
+/// fitting all flags. The derive requires the 'derive' Cargo feature and
+/// rejects discriminants that are not integer literals, not powers of two or
+/// used by multiple flags. The enum has to implement `Copy`.
+///
+/// ```
+/// use atomig::{AtomFlags, Atomic, Ordering};
+/// # #[cfg(feature = "derive")]
+/// # fn main() {
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, AtomFlags)]
+/// enum Perm {
+///     Read = 1,
+///     Write = 2,
+///     Exec = 4,
+/// }
+///
+/// let perms = Atomic::new(PermSet::from(Perm::Read));
+/// perms.fetch_insert(Perm::Exec, Ordering::SeqCst);
+/// assert!(perms.contains(Perm::Exec, Ordering::SeqCst));
+/// assert!(!perms.contains(Perm::Write, Ordering::SeqCst));
+///
+/// let set = perms.fetch_remove(Perm::Read, Ordering::SeqCst);
+/// assert_eq!(set.iter().collect::<Vec<_>>(), [Perm::Read, Perm::Exec]);
+/// # }
+///
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+pub trait AtomFlags: Copy {
+    /// The set of flags.
+    type Set: Atom;
+
+    /// Returns the set only containing `self`.
+    fn into_set(self) -> Self::Set;
+
+    /// Returns the set containing all flags except `self`.
+    fn complement_set(self) -> Self::Set;
+
+    /// Returns `true` if `set` contains `self`.
+    fn is_in(self, set: Self::Set) -> bool;
+}
+
+/// `Atom`s with a representation that `pack` never returns, which is used
+/// to represent `None` in `Option<Self>`.
+///
+/// For all types implementing this trait, `Option<Self>` implements [`Atom`]
+/// with the same `Repr` as `Self`, so `Atomic<Option<Self>>` does not need a
+/// wider representation. This is implemented for `NonNull<T>` (with the null
+/// pointer as niche) and for the `NonZero*` types (with zero as niche).
+///
+/// If `pack` returns `NICHE` anyway, that value is read as `None`. This is
+/// not unsafe, but most likely not what you want. `derive(Atom)` implements
+/// this trait if a niche is specified via `#[atom(niche = _)]`. For C-like
+/// enums with literal discriminants, it also checks that the niche is not
+/// used by any variant. For enums with fields, the niche's tag must belong to
+/// no variant (or to a unit variant, with non-zero lower bits), and for packed
+/// and bitfield structs, it must set a bit above all fields. Enums with an
+/// `#[atom(other)]` variant cannot have a niche at all.
+///
+/// ```compile_fail
+/// # use atomig::Atom;
+/// #[derive(Atom)]
+/// #[atom(niche = 0x1_05)]  // error: `Set(5)` is packed to `0x1_05`
+/// enum Slot { Empty, Set(u8) }
+/// ```
+///
+/// ```compile_fail
+/// # use atomig::Atom;
+/// #[derive(Atom)]
+/// #[atom(niche = 0xFF)]  // error: `Other(0xFF)` is packed to `0xFF`
+/// enum Status { Ok = 0, #[atom(other)] Other(u8) }
+/// ```
+///
+/// ```
+/// use atomig::{Atom, AtomNiche, Atomic, Ordering};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Percent(u8);
+///
+/// impl Atom for Percent {
+///     type Repr = u8;
+///     fn pack(self) -> u8 {
+///         self.0.min(100)
+///     }
+///     fn unpack(src: u8) -> Self {
+///         Percent(src)
+///     }
+/// }
+///
+/// impl AtomNiche for Percent {
+///     const NICHE: u8 = u8::MAX;
+/// }
+///
+/// let progress = Atomic::new(None);
+/// assert_eq!(progress.swap(Some(Percent(30)), Ordering::SeqCst), None);
+/// assert_eq!(progress.take(Ordering::SeqCst), Some(Percent(30)));
+/// ```
+pub trait AtomNiche: Atom {
+    /// A representation that is never returned by `pack`.
+    const NICHE: Self::Repr;
+}
+
 
 
 // ===============================================================================================
@@ -320,7 +816,9 @@ where
 /// there are many bit patterns that represent NaN. So the atomic might indeed
 /// store a NaN representation at a moment, but `compare_exchange` called with
 /// `current = NaN` might not swap, because both NaN differ in the bit
-/// representation.
+/// representation. Use [`compare_exchange_eq`][Atomic::compare_exchange_eq] or
+/// [`compare_exchange_by`][Atomic::compare_exchange_by] to compare values
+/// instead.
 ///
 /// The interface of this type very closely matches the interface of the atomic
 /// types in `std::sync::atomic`. The documentation was copied (and slightly
@@ -347,7 +845,8 @@ impl<T: Atom> Atomic<T> {
     /// the only way for this to be a `const fn` is
     /// to take the underyling atomic impl type directly.
     ///
-    /// This allows `static` `Atomic`s to be created.
+    /// This allows `static` `Atomic`s to be created. For many derived types,
+    /// the [`atomic!`][crate::atomic] macro is more convenient.
     ///
     /// # Examples
     ///
@@ -391,6 +890,29 @@ impl<T: Atom> Atomic<T> {
         T::unpack(T::Repr::load(&self.0, order))
     }
 
+    /// Loads the value from the atomic, like `load`, but returns an error
+    /// instead of panicking if the stored representation is not valid for `T`
+    /// (see [`Atom::try_unpack`]).
+    ///
+    /// This is useful if the atomic can be modified by less-trusted code, e.g.
+    /// via shared memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is `Release` or `AcqRel`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new('x');
+    /// assert_eq!(x.try_load(Ordering::SeqCst), Ok('x'));
+    /// ```
+    pub fn try_load(&self, order: Ordering) -> Result<T, InvalidRepr<T::Repr>> {
+        try_unpack(T::Repr::load(&self.0, order))
+    }
+
     /// Stores a value into the atomic.
     ///
     /// `store` takes an [`Ordering`] argument which describes the memory
@@ -434,6 +956,22 @@ impl<T: Atom> Atomic<T> {
         T::unpack(T::Repr::swap(&self.0, v.pack(), order))
     }
 
+    /// Stores a value into the atomic, like `swap`, but returns an error
+    /// instead of panicking if the previous representation is not valid for
+    /// `T` (see [`Atom::try_unpack`]). The new value is stored in either case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new('a');
+    /// assert_eq!(x.try_swap('b', Ordering::SeqCst), Ok('a'));
+    /// ```
+    pub fn try_swap(&self, v: T, order: Ordering) -> Result<T, InvalidRepr<T::Repr>> {
+        try_unpack(T::Repr::swap(&self.0, v.pack(), order))
+    }
+
     /// Stores a value into the atomic if the current value is the same as the
     /// `current` value.
     ///
@@ -481,6 +1019,38 @@ impl<T: Atom> Atomic<T> {
             .map_err(T::unpack)
     }
 
+    /// Like `compare_exchange`, but returns an error instead of panicking if
+    /// the previous representation is not valid for `T` (see
+    /// [`Atom::try_unpack`]).
+    ///
+    /// The outer `Result` is `Err` if the previous representation is invalid,
+    /// in which case the comparison has failed. Otherwise, it contains the
+    /// result of `compare_exchange`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new('a');
+    /// let res = x.try_compare_exchange('a', 'b', Ordering::SeqCst, Ordering::SeqCst);
+    /// assert_eq!(res, Ok(Ok('a')));
+    /// let res = x.try_compare_exchange('a', 'c', Ordering::SeqCst, Ordering::SeqCst);
+    /// assert_eq!(res, Ok(Err('b')));
+    /// ```
+    pub fn try_compare_exchange(
+        &self,
+        current: T,
+        new: T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Result<T, T>, InvalidRepr<T::Repr>> {
+        match T::Repr::compare_exchange(&self.0, current.pack(), new.pack(), success, failure) {
+            Ok(prev) => try_unpack(prev).map(Ok),
+            Err(prev) => try_unpack(prev).map(Err),
+        }
+    }
+
     /// Stores a value into the atomic if the current value is the same as the
     /// `current` value.
     ///
@@ -526,52 +1096,414 @@ impl<T: Atom> Atomic<T> {
             .map_err(T::unpack)
     }
 
-    /// Fetches the value, and applies a function to it that returns an
-    /// optional new value. Returns a `Result` of `Ok(previous_value)` if the
-    /// function returned `Some(_)`, else `Err(previous_value)`.
+    /// Stores a value into the atomic if the current value is equal to
+    /// `current` according to `PartialEq`.
     ///
-    /// Note: This may call the function multiple times if the value has been
-    /// changed from other threads in the meantime, as long as the function
-    /// returns `Some(_)`, but the function will have been applied but once to
-    /// the stored value.
-    ///
-    /// `fetch_update` takes two [`Ordering`] arguments to describe the memory
-    /// ordering of this operation. The first describes the required ordering
-    /// for loads and failed updates while the second describes the required
-    /// ordering when the operation finally succeeds. Beware that this is
-    /// different from the two modes in `compare_exchange`!
+    /// In contrast to `compare_exchange`, which compares the bits of the
+    /// representation, this succeeds whenever the current value compares
+    /// equal to `current`, e.g. for `0.0` and `-0.0`, or for different
+    /// representations of the same value of a user type. Internally, the
+    /// observed bits are used for the actual compare-exchange, which is
+    /// retried if they changed in the meantime. Note that NaN is not equal to
+    /// itself, so this always fails for NaN.
     ///
-    /// Using `Acquire` as success ordering makes the store part of this
-    /// operation `Relaxed`, and using `Release` makes the final successful
-    /// load `Relaxed`. The (failed) load ordering can only be `SeqCst`,
-    /// `Acquire` or `Relaxed` and must be equivalent to or weaker than the
-    /// success ordering.
+    /// The return value is a result indicating whether the new value was
+    /// written and containing the previous value. `compare_exchange_eq` takes
+    /// two [`Ordering`] arguments to describe the memory ordering of this
+    /// operation, just like `compare_exchange`.
     ///
     /// # Examples
     ///
     /// ```
     /// use atomig::{Atomic, Ordering};
     ///
-    /// let x = Atomic::new(7);
-    /// assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |_| None), Err(7));
-    /// assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| Some(x + 1)), Ok(7));
-    /// assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| Some(x + 1)), Ok(8));
-    /// assert_eq!(x.load(Ordering::SeqCst), 9);
+    /// let x = Atomic::new(-0.0f64);
+    /// assert!(x.compare_exchange(0.0, 1.0, Ordering::SeqCst, Ordering::SeqCst).is_err());
+    /// assert!(x.compare_exchange_eq(0.0, 1.0, Ordering::SeqCst, Ordering::SeqCst).is_ok());
+    /// assert_eq!(x.load(Ordering::SeqCst), 1.0);
     /// ```
-    pub fn fetch_update<F>(
+    pub fn compare_exchange_eq(
         &self,
-        set_order: Ordering,
-        fetch_order: Ordering,
-        mut f: F,
+        current: T,
+        new: T,
+        success: Ordering,
+        failure: Ordering,
     ) -> Result<T, T>
     where
-        F: FnMut(T) -> Option<T>
+        T: PartialEq,
     {
-        let f = |repr| f(T::unpack(repr)).map(Atom::pack);
-        T::Repr::fetch_update(&self.0, set_order, fetch_order, f)
+        self.compare_exchange_by(current, new, |a, b| a == b, success, failure)
+    }
+
+    /// Stores a value into the atomic if `eq(&current_value, &current)`
+    /// returns `true`.
+    ///
+    /// This is the generalization of
+    /// [`compare_exchange_eq`][Atomic::compare_exchange_eq] with a custom
+    /// equality predicate. `eq` may be called multiple times if the value is
+    /// changed by other threads in the meantime.
+    ///
+    /// The return value is a result indicating whether the new value was
+    /// written and containing the previous value. `compare_exchange_by` takes
+    /// two [`Ordering`] arguments to describe the memory ordering of this
+    /// operation, just like `compare_exchange`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(f32::NAN);
+    /// let both_nan = |a: &f32, b: &f32| a == b || (a.is_nan() && b.is_nan());
+    /// let (success, failure) = (Ordering::SeqCst, Ordering::SeqCst);
+    /// assert!(x.compare_exchange_by(-f32::NAN, 3.0, both_nan, success, failure).is_ok());
+    /// assert_eq!(x.load(Ordering::SeqCst), 3.0);
+    /// ```
+    pub fn compare_exchange_by<F>(
+        &self,
+        current: T,
+        new: T,
+        mut eq: F,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<T, T>
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let new = new.pack();
+        let f = |old| if eq(&T::unpack(old), &current) { Some(new) } else { None };
+        T::Repr::fetch_update(&self.0, success, failure, f)
+            .map(T::unpack)
+            .map_err(T::unpack)
+    }
+
+    /// Fetches the value, and applies a function to it that returns an
+    /// optional new value. Returns a `Result` of `Ok(previous_value)` if the
+    /// function returned `Some(_)`, else `Err(previous_value)`.
+    ///
+    /// Note: This may call the function multiple times if the value has been
+    /// changed from other threads in the meantime, as long as the function
+    /// returns `Some(_)`, but the function will have been applied but once to
+    /// the stored value.
+    ///
+    /// `fetch_update` takes two [`Ordering`] arguments to describe the memory
+    /// ordering of this operation. The first describes the required ordering
+    /// for loads and failed updates while the second describes the required
+    /// ordering when the operation finally succeeds. Beware that this is
+    /// different from the two modes in `compare_exchange`!
+    ///
+    /// Using `Acquire` as success ordering makes the store part of this
+    /// operation `Relaxed`, and using `Release` makes the final successful
+    /// load `Relaxed`. The (failed) load ordering can only be `SeqCst`,
+    /// `Acquire` or `Relaxed` and must be equivalent to or weaker than the
+    /// success ordering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(7);
+    /// assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |_| None), Err(7));
+    /// assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| Some(x + 1)), Ok(7));
+    /// assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| Some(x + 1)), Ok(8));
+    /// assert_eq!(x.load(Ordering::SeqCst), 9);
+    /// ```
+    pub fn fetch_update<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: F,
+    ) -> Result<T, T>
+    where
+        F: FnMut(T) -> Option<T>
+    {
+        let f = |repr| f(T::unpack(repr)).map(Atom::pack);
+        T::Repr::fetch_update(&self.0, set_order, fetch_order, f)
+            .map(Atom::unpack)
+            .map_err(Atom::unpack)
+    }
+
+    /// Replaces the current value with `f(current)`, returning the previous
+    /// and the new value.
+    ///
+    /// This is a compare-exchange loop, so `f` may be called multiple times
+    /// if the value has been changed from other threads in the meantime, but
+    /// only one result of `f` is stored.
+    ///
+    /// `update` takes a single [`Ordering`] argument which describes the
+    /// memory ordering of the successful update. All ordering modes are
+    /// possible. The ordering of the loads is derived from it like for the
+    /// failure ordering of `compare_exchange` in `std`: `Release` becomes
+    /// `Relaxed` and `AcqRel` becomes `Acquire`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(7);
+    /// assert_eq!(x.update(Ordering::SeqCst, |x| x * 3), (7, 21));
+    /// assert_eq!(x.load(Ordering::SeqCst), 21);
+    /// ```
+    pub fn update<F>(&self, order: Ordering, mut f: F) -> (T, T)
+    where
+        F: FnMut(T) -> T,
+    {
+        let mut new = None;
+        let prev = impls::fetch_op(&self.0, order, |repr| {
+            let repr = f(T::unpack(repr)).pack();
+            new = Some(repr);
+            repr
+        });
+        // `fetch_op` calls the closure at least once.
+        let new = new.expect("closure was not called in `Atomic::update`");
+        (T::unpack(prev), T::unpack(new))
+    }
+
+    /// Replaces the current value with `f(current)`, returning the new value.
+    ///
+    /// This is [`update`][Atomic::update] without returning the previous
+    /// value; see there for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(3u8);
+    /// assert_eq!(x.update_and_fetch(Ordering::AcqRel, |x| x.saturating_mul(100)), 255);
+    /// ```
+    pub fn update_and_fetch<F>(&self, order: Ordering, f: F) -> T
+    where
+        F: FnMut(T) -> T,
+    {
+        self.update(order, f).1
+    }
+
+    /// Replaces the current value with `f(current)` if that returns `Some(_)`.
+    ///
+    /// Returns `Ok(previous_value)` if the value was replaced and
+    /// `Err(previous_value)` if `f` returned `None`. This is like
+    /// [`fetch_update`][Atomic::fetch_update], but with a single
+    /// [`Ordering`] from which the ordering of the loads is derived as
+    /// described for [`update`][Atomic::update].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(254u8);
+    /// assert_eq!(x.try_update(Ordering::SeqCst, |x| x.checked_add(1)), Ok(254));
+    /// assert_eq!(x.try_update(Ordering::SeqCst, |x| x.checked_add(1)), Err(255));
+    /// ```
+    pub fn try_update<F>(&self, order: Ordering, mut f: F) -> Result<T, T>
+    where
+        F: FnMut(T) -> Option<T>,
+    {
+        let f = |repr| f(T::unpack(repr)).map(Atom::pack);
+        impls::fetch_checked_op(&self.0, order, f)
+            .map(Atom::unpack)
+            .map_err(Atom::unpack)
+    }
+
+    /// Stores `new` if `pred(&current)` returns `true`.
+    ///
+    /// Returns `Ok(previous_value)` if `new` was stored and
+    /// `Err(previous_value)` otherwise. `pred` may be called multiple times
+    /// if the value has been changed from other threads in the meantime. The
+    /// ordering of the loads is derived from `order` as described for
+    /// [`update`][Atomic::update].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let deadline = Atomic::new(100u64);
+    /// assert_eq!(deadline.store_if(|&d| d > 80, 80, Ordering::SeqCst), Ok(100));
+    /// assert_eq!(deadline.store_if(|&d| d > 90, 90, Ordering::SeqCst), Err(80));
+    /// ```
+    pub fn store_if<P>(&self, mut pred: P, new: T, order: Ordering) -> Result<T, T>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let new = new.pack();
+        let f = |repr| if pred(&T::unpack(repr)) { Some(new) } else { None };
+        impls::fetch_checked_op(&self.0, order, f)
             .map(Atom::unpack)
             .map_err(Atom::unpack)
     }
+
+    /// Maximum with the current value according to `compare`, returning the
+    /// previous value.
+    ///
+    /// `val` is stored if `compare(&val, &current)` returns `Greater`. This
+    /// works for all types, not only for `AtomInteger` and `AtomFloat` types
+    /// like [`fetch_max`][Atomic::fetch_max], which compares the
+    /// representations. For types implementing `Ord`,
+    /// [`fetch_max_ord`][Atomic::fetch_max_ord] passes `Ord::cmp` as
+    /// `compare`. This is a compare-exchange loop which stops without
+    /// storing as soon as `val` is not greater than the current value.
+    ///
+    /// `fetch_max_by` takes an [`Ordering`] argument which describes the
+    /// memory ordering of the successful update. All ordering modes are
+    /// possible. The ordering of the loads is derived from it as described
+    /// for [`update`][Atomic::update].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new('m');
+    /// assert_eq!(x.fetch_max_by('z', Ord::cmp, Ordering::SeqCst), 'm');
+    /// assert_eq!(x.fetch_max_by('a', Ord::cmp, Ordering::SeqCst), 'z');
+    /// assert_eq!(x.load(Ordering::SeqCst), 'z');
+    /// ```
+    pub fn fetch_max_by<F>(&self, val: T, mut compare: F, order: Ordering) -> T
+    where
+        F: FnMut(&T, &T) -> core::cmp::Ordering,
+    {
+        self.fetch_replace_if(val, order, |val, cur| compare(val, cur).is_gt())
+    }
+
+    /// Minimum with the current value according to `compare`, returning the
+    /// previous value.
+    ///
+    /// `val` is stored if `compare(&val, &current)` returns `Less`. See
+    /// [`fetch_max_by`][Atomic::fetch_max_by] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new('m');
+    /// assert_eq!(x.fetch_min_by('z', Ord::cmp, Ordering::SeqCst), 'm');
+    /// assert_eq!(x.fetch_min_by('a', Ord::cmp, Ordering::SeqCst), 'm');
+    /// assert_eq!(x.load(Ordering::SeqCst), 'a');
+    /// ```
+    pub fn fetch_min_by<F>(&self, val: T, mut compare: F, order: Ordering) -> T
+    where
+        F: FnMut(&T, &T) -> core::cmp::Ordering,
+    {
+        self.fetch_replace_if(val, order, |val, cur| compare(val, cur).is_lt())
+    }
+
+    /// Maximum with the current value according to the key returned by `f`,
+    /// returning the previous value.
+    ///
+    /// `val` is stored if `f(&val) > f(&current)`. See
+    /// [`fetch_max_by`][Atomic::fetch_max_by] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(-3i32);
+    /// assert_eq!(x.fetch_max_by_key(2, |v| v.abs(), Ordering::SeqCst), -3);
+    /// assert_eq!(x.fetch_max_by_key(-5, |v| v.abs(), Ordering::SeqCst), -3);
+    /// assert_eq!(x.load(Ordering::SeqCst), -5);
+    /// ```
+    pub fn fetch_max_by_key<K, F>(&self, val: T, mut f: F, order: Ordering) -> T
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.fetch_replace_if(val, order, |val, cur| f(val) > f(cur))
+    }
+
+    /// Minimum with the current value according to the key returned by `f`,
+    /// returning the previous value.
+    ///
+    /// `val` is stored if `f(&val) < f(&current)`. See
+    /// [`fetch_max_by`][Atomic::fetch_max_by] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(-3i32);
+    /// assert_eq!(x.fetch_min_by_key(-1, |v| v.abs(), Ordering::SeqCst), -3);
+    /// assert_eq!(x.fetch_min_by_key(1, |v| v.abs(), Ordering::SeqCst), -1);
+    /// assert_eq!(x.load(Ordering::SeqCst), -1);
+    /// ```
+    pub fn fetch_min_by_key<K, F>(&self, val: T, mut f: F, order: Ordering) -> T
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.fetch_replace_if(val, order, |val, cur| f(val) < f(cur))
+    }
+
+    /// Maximum with the current value according to `Ord`, returning the
+    /// previous value.
+    ///
+    /// Same as [`fetch_max_by`][Atomic::fetch_max_by] with `Ord::cmp`. Unlike
+    /// [`fetch_max`][Atomic::fetch_max], this compares the values instead of
+    /// their representations and is always a compare-exchange loop.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new('m');
+    /// assert_eq!(x.fetch_max_ord('z', Ordering::SeqCst), 'm');
+    /// assert_eq!(x.fetch_max_ord('a', Ordering::SeqCst), 'z');
+    /// assert_eq!(x.load(Ordering::SeqCst), 'z');
+    /// ```
+    pub fn fetch_max_ord(&self, val: T, order: Ordering) -> T
+    where
+        T: Ord,
+    {
+        self.fetch_max_by(val, Ord::cmp, order)
+    }
+
+    /// Minimum with the current value according to `Ord`, returning the
+    /// previous value.
+    ///
+    /// Same as [`fetch_min_by`][Atomic::fetch_min_by] with `Ord::cmp`. See
+    /// [`fetch_max_ord`][Atomic::fetch_max_ord] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new('m');
+    /// assert_eq!(x.fetch_min_ord('z', Ordering::SeqCst), 'm');
+    /// assert_eq!(x.fetch_min_ord('a', Ordering::SeqCst), 'm');
+    /// assert_eq!(x.load(Ordering::SeqCst), 'a');
+    /// ```
+    pub fn fetch_min_ord(&self, val: T, order: Ordering) -> T
+    where
+        T: Ord,
+    {
+        self.fetch_min_by(val, Ord::cmp, order)
+    }
+
+    /// Stores `val` if `replace(&val, &current)` returns `true`, returning the
+    /// previous value.
+    fn fetch_replace_if(
+        &self,
+        val: T,
+        order: Ordering,
+        mut replace: impl FnMut(&T, &T) -> bool,
+    ) -> T {
+        // `T` is not necessarily `Clone`, so we get a second `val` by
+        // unpacking its representation.
+        let new = val.pack();
+        let val = T::unpack(new);
+        let f = |repr| if replace(&val, &T::unpack(repr)) { Some(new) } else { None };
+        match impls::fetch_checked_op(&self.0, order, f) {
+            Ok(prev) | Err(prev) => T::unpack(prev),
+        }
+    }
 }
 
 // TODO: the `where` bound should not be necessary as the `AtomLogic` trait
@@ -679,20 +1611,14 @@ where
     pub fn fetch_xor(&self, val: T, order: Ordering) -> T {
         T::unpack(T::Repr::fetch_xor(&self.0, val.pack(), order))
     }
-}
-
 
-// TODO: the `where` bound should not be necessary as the `AtomInteger` trait
-// already specifies this. Maybe we can fix this in the future.
-impl<T: AtomInteger> Atomic<T>
-where
-    T::Repr: PrimitiveAtomInteger,
-{
-    /// Adds to the current value, returning the previous value.
+    /// Bitwise "not" of the current value.
     ///
-    /// This operation wraps around on overflow.
+    /// Flips the bits of the representation selected by
+    /// [`AtomLogic::NOT_MASK`] (all bits by default) with a single atomic
+    /// "xor" and returns the previous value.
     ///
-    /// `fetch_add` takes an [`Ordering`] argument which describes the memory
+    /// `fetch_not` takes an [`Ordering`] argument which describes the memory
     /// ordering of this operation. All ordering modes are possible. Note that
     /// using `Acquire` makes the store part of this operation `Relaxed`, and
     /// using `Release` makes the load part `Relaxed`.
@@ -702,91 +1628,516 @@ where
     /// ```
     /// use atomig::{Atomic, Ordering};
     ///
-    /// let x = Atomic::new(0);
-    /// assert_eq!(x.fetch_add(10, Ordering::SeqCst), 0);
-    /// assert_eq!(x.load(Ordering::SeqCst), 10);
+    /// let x = Atomic::new(0b1010_0101u8);
+    /// assert_eq!(x.fetch_not(Ordering::SeqCst), 0b1010_0101);
+    /// assert_eq!(x.load(Ordering::SeqCst), 0b0101_1010);
+    ///
+    /// let flag = Atomic::new(true);
+    /// flag.fetch_not(Ordering::SeqCst);
+    /// assert_eq!(flag.load(Ordering::SeqCst), false);
     /// ```
-    pub fn fetch_add(&self, val: T, order: Ordering) -> T {
-        T::unpack(T::Repr::fetch_add(&self.0, val.pack(), order))
+    pub fn fetch_not(&self, order: Ordering) -> T {
+        T::unpack(T::Repr::fetch_xor(&self.0, T::NOT_MASK, order))
     }
 
-    /// Subtracts from the current value, returning the previous value.
+    /// Sets bit `n` of the representation, returning its previous state.
     ///
-    /// This operation wraps around on overflow.
+    /// This is `fetch_or` with only bit `n` set, which compiles to a single
+    /// instruction on some platforms (e.g. `lock bts` on x86). Bit 0 is the
+    /// least significant bit.
     ///
-    /// `fetch_sub` takes an [`Ordering`] argument which describes the memory
-    /// ordering of this operation. All ordering modes are possible. Note that
-    /// using `Acquire` makes the store part of this operation `Relaxed`, and
-    /// using `Release` makes the load part `Relaxed`.
+    /// `fetch_bit_set` takes an [`Ordering`] argument which describes the
+    /// memory ordering of this operation. All ordering modes are possible.
+    /// Note that using `Acquire` makes the store part of this operation
+    /// `Relaxed`, and using `Release` makes the load part `Relaxed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not smaller than the number of bits of the
+    /// representation.
     ///
     /// # Examples
     ///
     /// ```
     /// use atomig::{Atomic, Ordering};
     ///
-    /// let x = Atomic::new(20);
-    /// assert_eq!(x.fetch_sub(10, Ordering::SeqCst), 20);
-    /// assert_eq!(x.load(Ordering::SeqCst), 10);
+    /// let slots = Atomic::new(0b0101u64);
+    /// assert_eq!(slots.fetch_bit_set(1, Ordering::SeqCst), false);
+    /// assert_eq!(slots.fetch_bit_set(2, Ordering::SeqCst), true);
+    /// assert_eq!(slots.load(Ordering::SeqCst), 0b0111);
     /// ```
-    pub fn fetch_sub(&self, val: T, order: Ordering) -> T {
-        T::unpack(T::Repr::fetch_sub(&self.0, val.pack(), order))
+    pub fn fetch_bit_set(&self, n: u32, order: Ordering) -> bool {
+        let mask = Self::bit_mask(n, "fetch_bit_set");
+        Self::has_bit(T::Repr::fetch_or(&self.0, T::Repr::from_bits(mask), order), mask)
     }
 
-    /// Maximum with the current value.
+    /// Clears bit `n` of the representation, returning its previous state.
     ///
-    /// Finds the maximum of the current value and the argument `val`, and sets
-    /// the new value to the result.
+    /// This is `fetch_and` with all bits except bit `n` set, which compiles
+    /// to a single instruction on some platforms (e.g. `lock btr` on x86).
+    /// Bit 0 is the least significant bit.
     ///
-    /// Returns the previous value.
+    /// `fetch_bit_clear` takes an [`Ordering`] argument which describes the
+    /// memory ordering of this operation. All ordering modes are possible.
+    /// Note that using `Acquire` makes the store part of this operation
+    /// `Relaxed`, and using `Release` makes the load part `Relaxed`.
     ///
-    /// `fetch_max` takes an [`Ordering`] argument which describes the memory
-    /// ordering of this operation. All ordering modes are possible. Note that
-    /// using `Acquire` makes the store part of this operation `Relaxed`, and
-    /// using `Release` makes the load part `Relaxed`.
+    /// # Panics
+    ///
+    /// Panics if `n` is not smaller than the number of bits of the
+    /// representation.
     ///
     /// # Examples
     ///
     /// ```
     /// use atomig::{Atomic, Ordering};
     ///
-    /// let foo = Atomic::new(23);
-    /// assert_eq!(foo.fetch_max(42, Ordering::SeqCst), 23);
-    /// assert_eq!(foo.load(Ordering::SeqCst), 42);
+    /// let slots = Atomic::new(0b0101u64);
+    /// assert_eq!(slots.fetch_bit_clear(2, Ordering::SeqCst), true);
+    /// assert_eq!(slots.fetch_bit_clear(1, Ordering::SeqCst), false);
+    /// assert_eq!(slots.load(Ordering::SeqCst), 0b0001);
     /// ```
+    pub fn fetch_bit_clear(&self, n: u32, order: Ordering) -> bool {
+        let mask = Self::bit_mask(n, "fetch_bit_clear");
+        Self::has_bit(T::Repr::fetch_and(&self.0, T::Repr::from_bits(!mask), order), mask)
+    }
+
+    /// Flips bit `n` of the representation, returning its previous state.
     ///
-    /// If you want to obtain the maximum value in one step, you can use the
-    /// following:
+    /// This is `fetch_xor` with only bit `n` set, which compiles to a single
+    /// instruction on some platforms (e.g. `lock btc` on x86). Bit 0 is the
+    /// least significant bit.
+    ///
+    /// `fetch_bit_toggle` takes an [`Ordering`] argument which describes the
+    /// memory ordering of this operation. All ordering modes are possible.
+    /// Note that using `Acquire` makes the store part of this operation
+    /// `Relaxed`, and using `Release` makes the load part `Relaxed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not smaller than the number of bits of the
+    /// representation.
+    ///
+    /// # Examples
     ///
     /// ```
     /// use atomig::{Atomic, Ordering};
     ///
-    /// let foo = Atomic::new(23);
-    /// let bar = 42;
-    /// let max_foo = foo.fetch_max(bar, Ordering::SeqCst).max(bar);
-    /// assert!(max_foo == 42);
+    /// let slots = Atomic::new(0b0101u8);
+    /// assert_eq!(slots.fetch_bit_toggle(0, Ordering::SeqCst), true);
+    /// assert_eq!(slots.fetch_bit_toggle(7, Ordering::SeqCst), false);
+    /// assert_eq!(slots.load(Ordering::SeqCst), 0b1000_0100);
     /// ```
-    pub fn fetch_max(&self, val: T, order: Ordering) -> T {
-        T::unpack(T::Repr::fetch_max(&self.0, val.pack(), order))
+    pub fn fetch_bit_toggle(&self, n: u32, order: Ordering) -> bool {
+        let mask = Self::bit_mask(n, "fetch_bit_toggle");
+        Self::has_bit(T::Repr::fetch_xor(&self.0, T::Repr::from_bits(mask), order), mask)
     }
 
-    /// Minimum with the current value.
+    /// Returns whether bit `n` of the representation is currently set.
     ///
-    /// Finds the minimum of the current value and the argument `val`, and sets
-    /// the new value to the result.
+    /// `bit_test` takes an [`Ordering`] argument which describes the memory
+    /// ordering of this operation. Possible values are `SeqCst`, `Acquire`
+    /// and `Relaxed`.
     ///
-    /// Returns the previous value.
+    /// # Panics
     ///
-    /// `fetch_min` takes an [`Ordering`] argument which describes the memory
-    /// ordering of this operation. All ordering modes are possible. Note that
-    /// using `Acquire` makes the store part of this operation `Relaxed`, and
-    /// using `Release` makes the load part `Relaxed`.
+    /// Panics if `order` is `Release` or `AcqRel`, or if `n` is not smaller
+    /// than the number of bits of the representation.
     ///
     /// # Examples
     ///
     /// ```
     /// use atomig::{Atomic, Ordering};
     ///
-    /// let foo = Atomic::new(23);
+    /// let slots = Atomic::new(0b0100u32);
+    /// assert!(slots.bit_test(2, Ordering::SeqCst));
+    /// assert!(!slots.bit_test(31, Ordering::SeqCst));
+    /// ```
+    pub fn bit_test(&self, n: u32, order: Ordering) -> bool {
+        let mask = Self::bit_mask(n, "bit_test");
+        Self::has_bit(T::Repr::load(&self.0, order), mask)
+    }
+
+    /// Replaces the bits selected by `mask` with the corresponding bits of
+    /// `value`, returning the previous value.
+    ///
+    /// The new value is `(old & !mask) | (value & mask)`, i.e. all bits not
+    /// set in `mask` are left unchanged. This is useful if several logical
+    /// fields share one atomic value.
+    ///
+    /// This is a compare-exchange loop. `fetch_store_masked` takes an
+    /// [`Ordering`] argument which describes the memory ordering of this
+    /// operation. All ordering modes are possible. Note that using `Acquire`
+    /// makes the store part of this operation `Relaxed`, and using `Release`
+    /// makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(0xAB_CDu16);
+    /// assert_eq!(x.fetch_store_masked(0x0F_F0, 0x12_34, Ordering::SeqCst), 0xAB_CD);
+    /// assert_eq!(x.load(Ordering::SeqCst), 0xA2_3D);
+    /// ```
+    pub fn fetch_store_masked(&self, mask: T, value: T, order: Ordering) -> T {
+        let (mask, value) = (mask.pack().into_bits(), value.pack().into_bits());
+        let f = |old: T::Repr| T::Repr::from_bits((old.into_bits() & !mask) | (value & mask));
+        T::unpack(impls::fetch_op(&self.0, order, f))
+    }
+
+    /// Like `compare_exchange`, but only compares and replaces the bits
+    /// selected by `mask`.
+    ///
+    /// If `old & mask == current & mask`, the value is set to
+    /// `(old & !mask) | (new & mask)`, leaving all bits not set in `mask`
+    /// unchanged, whatever their value is. The return value is a result
+    /// indicating whether the new value was written and containing the
+    /// previous value `old`.
+    ///
+    /// In contrast to `compare_exchange`, this is a compare-exchange loop that
+    /// retries as long as only unmasked bits get changed concurrently.
+    /// `compare_exchange_masked` takes two [`Ordering`] arguments to describe
+    /// the memory ordering of this operation. The first describes the
+    /// required ordering if the operation succeeds while the second describes
+    /// the required ordering when the operation fails. Using `Acquire` as
+    /// success ordering makes the store part of this operation `Relaxed`, and
+    /// using `Release` makes the successful load `Relaxed`. The failure
+    /// ordering can only be `SeqCst`, `Acquire` or `Relaxed` and must be
+    /// equivalent to or weaker than the success ordering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// // The lowest bit is a lock flag, the other bits are a counter.
+    /// let word = Atomic::new(0b1010_0000u8);
+    /// let lock = |w: &Atomic<u8>| {
+    ///     w.compare_exchange_masked(1, 0, 1, Ordering::Acquire, Ordering::Relaxed)
+    /// };
+    /// assert_eq!(lock(&word), Ok(0b1010_0000));
+    /// assert_eq!(lock(&word), Err(0b1010_0001));
+    /// ```
+    pub fn compare_exchange_masked(
+        &self,
+        mask: T,
+        current: T,
+        new: T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<T, T> {
+        let mask = mask.pack().into_bits();
+        let (current, new) = (current.pack().into_bits() & mask, new.pack().into_bits() & mask);
+        let f = |old: T::Repr| {
+            let old = old.into_bits();
+            if old & mask == current {
+                Some(T::Repr::from_bits((old & !mask) | new))
+            } else {
+                None
+            }
+        };
+        T::Repr::fetch_update(&self.0, success, failure, f)
+            .map(T::unpack)
+            .map_err(T::unpack)
+    }
+
+    fn bit_mask(n: u32, method: &str) -> u64 {
+        assert!(
+            n < T::Repr::BITS,
+            "bit index {} out of range in `Atomic::{}` for a {}-bit representation",
+            n,
+            method,
+            T::Repr::BITS,
+        );
+        1 << n
+    }
+
+    fn has_bit(repr: T::Repr, mask: u64) -> bool {
+        repr.into_bits() & mask != 0
+    }
+
+    /// Adds `flag` to the current set of flags.
+    ///
+    /// This is `fetch_or` with the set only containing `flag`. See
+    /// [`AtomFlags`] for more information.
+    ///
+    /// Returns the previous set.
+    pub fn fetch_insert<F: AtomFlags<Set = T>>(&self, flag: F, order: Ordering) -> T {
+        self.fetch_or(flag.into_set(), order)
+    }
+
+    /// Removes `flag` from the current set of flags.
+    ///
+    /// This is `fetch_and` with all bits of the representation set except
+    /// those of `flag`, so bits that are not flags are kept. See
+    /// [`AtomFlags`] for more information.
+    ///
+    /// Returns the previous set.
+    pub fn fetch_remove<F: AtomFlags<Set = T>>(&self, flag: F, order: Ordering) -> T {
+        let mask = flag.into_set().pack().into_bits();
+        T::unpack(T::Repr::fetch_and(&self.0, T::Repr::from_bits(!mask), order))
+    }
+
+    /// Returns `true` if the current set of flags contains `flag`.
+    ///
+    /// `contains` takes an [`Ordering`] argument which describes the memory
+    /// ordering of this operation. Possible values are `SeqCst`, `Acquire`
+    /// and `Relaxed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is `Release` or `AcqRel`.
+    pub fn contains<F: AtomFlags<Set = T>>(&self, flag: F, order: Ordering) -> bool {
+        flag.is_in(self.load(order))
+    }
+}
+
+
+// These methods are available for `AtomInteger` and `AtomFloat` types. As
+// both would be overlapping inherent impls, they are dispatched via
+// `AtomArithmetic<K>`, with `K` selecting the implementation.
+impl<T: Atom> Atomic<T> {
+    /// Adds to the current value, returning the previous value.
+    ///
+    /// For [`AtomInteger`] types, this operation wraps around on overflow. For
+    /// [`AtomFloat`] types, this is a floating point addition.
+    ///
+    /// `fetch_add` takes an [`Ordering`] argument which describes the memory
+    /// ordering of this operation. All ordering modes are possible. Note that
+    /// using `Acquire` makes the store part of this operation `Relaxed`, and
+    /// using `Release` makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(0);
+    /// assert_eq!(x.fetch_add(10, Ordering::SeqCst), 0);
+    /// assert_eq!(x.load(Ordering::SeqCst), 10);
+    /// ```
+    pub fn fetch_add<K>(&self, val: T, order: Ordering) -> T
+    where
+        T: AtomArithmetic<K>,
+    {
+        T::fetch_add(&self.0, val, order)
+    }
+
+    /// Subtracts from the current value, returning the previous value.
+    ///
+    /// For [`AtomInteger`] types, this operation wraps around on overflow. For
+    /// [`AtomFloat`] types, this is a floating point subtraction.
+    ///
+    /// `fetch_sub` takes an [`Ordering`] argument which describes the memory
+    /// ordering of this operation. All ordering modes are possible. Note that
+    /// using `Acquire` makes the store part of this operation `Relaxed`, and
+    /// using `Release` makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(20);
+    /// assert_eq!(x.fetch_sub(10, Ordering::SeqCst), 20);
+    /// assert_eq!(x.load(Ordering::SeqCst), 10);
+    /// ```
+    pub fn fetch_sub<K>(&self, val: T, order: Ordering) -> T
+    where
+        T: AtomArithmetic<K>,
+    {
+        T::fetch_sub(&self.0, val, order)
+    }
+
+    /// Multiplies the current value, returning the previous value.
+    ///
+    /// For [`AtomInteger`] types, this operation wraps around on overflow. For
+    /// [`AtomFloat`] types, this is a floating point multiplication.
+    ///
+    /// This is a compare-exchange loop. `fetch_mul` takes an [`Ordering`]
+    /// argument which describes the memory ordering of this operation. All
+    /// ordering modes are possible. Note that using `Acquire` makes the store
+    /// part of this operation `Relaxed`, and using `Release` makes the load
+    /// part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(6u8);
+    /// assert_eq!(x.fetch_mul(7, Ordering::SeqCst), 6);
+    /// assert_eq!(x.load(Ordering::SeqCst), 42);
+    ///
+    /// let y = Atomic::new(1.5f32);
+    /// assert_eq!(y.fetch_mul(4.0, Ordering::SeqCst), 1.5);
+    /// assert_eq!(y.load(Ordering::SeqCst), 6.0);
+    /// ```
+    pub fn fetch_mul<K>(&self, val: T, order: Ordering) -> T
+    where
+        T: AtomArithmetic<K>,
+    {
+        T::fetch_mul(&self.0, val, order)
+    }
+
+    /// Divides the current value, returning the previous value.
+    ///
+    /// For [`AtomInteger`] types, this operation wraps around on overflow
+    /// (i.e. `MIN / -1` results in `MIN`). For [`AtomFloat`] types, this is a
+    /// floating point division.
+    ///
+    /// This is a compare-exchange loop. `fetch_div` takes an [`Ordering`]
+    /// argument which describes the memory ordering of this operation. All
+    /// ordering modes are possible. Note that using `Acquire` makes the store
+    /// part of this operation `Relaxed`, and using `Release` makes the load
+    /// part `Relaxed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `val` is represented by zero for [`AtomInteger`] types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(45);
+    /// assert_eq!(x.fetch_div(6, Ordering::SeqCst), 45);
+    /// assert_eq!(x.load(Ordering::SeqCst), 7);
+    /// ```
+    pub fn fetch_div<K>(&self, val: T, order: Ordering) -> T
+    where
+        T: AtomArithmetic<K>,
+    {
+        T::fetch_div(&self.0, val, order)
+    }
+
+    /// Replaces the current value by the remainder of dividing it by `val`,
+    /// returning the previous value.
+    ///
+    /// For [`AtomInteger`] types, this operation wraps around on overflow
+    /// (i.e. `MIN % -1` results in 0). For [`AtomFloat`] types, this is the
+    /// floating point remainder like the `%` operator.
+    ///
+    /// This is a compare-exchange loop. `fetch_rem` takes an [`Ordering`]
+    /// argument which describes the memory ordering of this operation. All
+    /// ordering modes are possible. Note that using `Acquire` makes the store
+    /// part of this operation `Relaxed`, and using `Release` makes the load
+    /// part `Relaxed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `val` is represented by zero for [`AtomInteger`] types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(45);
+    /// assert_eq!(x.fetch_rem(6, Ordering::SeqCst), 45);
+    /// assert_eq!(x.load(Ordering::SeqCst), 3);
+    /// ```
+    pub fn fetch_rem<K>(&self, val: T, order: Ordering) -> T
+    where
+        T: AtomArithmetic<K>,
+    {
+        T::fetch_rem(&self.0, val, order)
+    }
+
+    /// Negates the current value, returning the previous value.
+    ///
+    /// For [`AtomInteger`] types, this operation wraps around on overflow
+    /// (i.e. `-MIN` results in `MIN`) and is a compare-exchange loop. For
+    /// [`AtomFloat`] types, this flips the sign bit with a single atomic
+    /// "xor", so it also works for NaNs and zeros.
+    ///
+    /// `fetch_neg` takes an [`Ordering`] argument which describes the memory
+    /// ordering of this operation. All ordering modes are possible. Note that
+    /// using `Acquire` makes the store part of this operation `Relaxed`, and
+    /// using `Release` makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(2.5f64);
+    /// assert_eq!(x.fetch_neg(Ordering::SeqCst), 2.5);
+    /// assert_eq!(x.load(Ordering::SeqCst), -2.5);
+    ///
+    /// let y = Atomic::new(i8::MIN + 1);
+    /// assert_eq!(y.fetch_neg(Ordering::SeqCst), -127);
+    /// assert_eq!(y.load(Ordering::SeqCst), 127);
+    /// ```
+    pub fn fetch_neg<K>(&self, order: Ordering) -> T
+    where
+        T: AtomArithmetic<K>,
+    {
+        T::fetch_neg(&self.0, order)
+    }
+
+    /// Maximum with the current value.
+    ///
+    /// Finds the maximum of the current value and the argument `val`, and sets
+    /// the new value to the result. For [`AtomFloat`] types, NaN and signed
+    /// zeros are treated as described in the trait's documentation.
+    ///
+    /// Returns the previous value.
+    ///
+    /// `fetch_max` takes an [`Ordering`] argument which describes the memory
+    /// ordering of this operation. All ordering modes are possible. Note that
+    /// using `Acquire` makes the store part of this operation `Relaxed`, and
+    /// using `Release` makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let foo = Atomic::new(23);
+    /// assert_eq!(foo.fetch_max(42, Ordering::SeqCst), 23);
+    /// assert_eq!(foo.load(Ordering::SeqCst), 42);
+    /// ```
+    ///
+    /// If you want to obtain the maximum value in one step, you can use the
+    /// following:
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let foo = Atomic::new(23);
+    /// let bar = 42;
+    /// let max_foo = foo.fetch_max(bar, Ordering::SeqCst).max(bar);
+    /// assert!(max_foo == 42);
+    /// ```
+    pub fn fetch_max<K>(&self, val: T, order: Ordering) -> T
+    where
+        T: AtomArithmetic<K>,
+    {
+        T::fetch_max(&self.0, val, order)
+    }
+
+    /// Minimum with the current value.
+    ///
+    /// Finds the minimum of the current value and the argument `val`, and sets
+    /// the new value to the result. For [`AtomFloat`] types, NaN and signed
+    /// zeros are treated as described in the trait's documentation.
+    ///
+    /// Returns the previous value.
+    ///
+    /// `fetch_min` takes an [`Ordering`] argument which describes the memory
+    /// ordering of this operation. All ordering modes are possible. Note that
+    /// using `Acquire` makes the store part of this operation `Relaxed`, and
+    /// using `Release` makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let foo = Atomic::new(23);
     /// assert_eq!(foo.fetch_min(42, Ordering::Relaxed), 23);
     /// assert_eq!(foo.load(Ordering::Relaxed), 23);
     /// assert_eq!(foo.fetch_min(22, Ordering::Relaxed), 23);
@@ -804,9 +2155,462 @@ where
     /// let min_foo = foo.fetch_min(bar, Ordering::SeqCst).min(bar);
     /// assert!(min_foo == 12);
     /// ```
-    pub fn fetch_min(&self, val: T, order: Ordering) -> T {
-        T::unpack(T::Repr::fetch_min(&self.0, val.pack(), order))
+    pub fn fetch_min<K>(&self, val: T, order: Ordering) -> T
+    where
+        T: AtomArithmetic<K>,
+    {
+        T::fetch_min(&self.0, val, order)
+    }
+}
+
+// TODO: the `where` bound should not be necessary as the `AtomInteger` trait
+// already specifies this. Maybe we can fix this in the future.
+impl<T: AtomInteger> Atomic<T>
+where
+    T::Repr: PrimitiveAtomInteger,
+{
+    /// Adds to the current value unless that overflows, returning the
+    /// previous value.
+    ///
+    /// Returns `Ok(previous)` if the addition succeeded and
+    /// `Err(current)` without storing anything if it would overflow. For
+    /// types with [`AtomInteger::NON_ZERO`] (e.g. `Option<NonZeroU32>`), a
+    /// result of zero also counts as overflow, unless the current value
+    /// already is zero.
+    ///
+    /// This is a compare-exchange loop. `checked_fetch_add` takes an
+    /// [`Ordering`] argument which describes the memory ordering of this
+    /// operation. All ordering modes are possible. Note that using `Acquire`
+    /// makes the store part of this operation `Relaxed`, and using `Release`
+    /// makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(250u8);
+    /// assert_eq!(x.checked_fetch_add(5, Ordering::SeqCst), Ok(250));
+    /// assert_eq!(x.checked_fetch_add(1, Ordering::SeqCst), Err(255));
+    /// assert_eq!(x.load(Ordering::SeqCst), 255);
+    /// ```
+    pub fn checked_fetch_add(&self, val: T, order: Ordering) -> Result<T, T> {
+        let val = val.pack();
+        self.fetch_checked(order, |cur| cur.checked_add(val))
+    }
+
+    /// Subtracts from the current value unless that overflows, returning the
+    /// previous value.
+    ///
+    /// Returns `Ok(previous)` if the subtraction succeeded and
+    /// `Err(current)` without storing anything if it would overflow. For
+    /// types with [`AtomInteger::NON_ZERO`] (e.g. `Option<NonZeroU32>`), a
+    /// result of zero also counts as overflow, unless the current value
+    /// already is zero. This makes it useful for reference counts.
+    ///
+    /// This is a compare-exchange loop. `checked_fetch_sub` takes an
+    /// [`Ordering`] argument which describes the memory ordering of this
+    /// operation. All ordering modes are possible. Note that using `Acquire`
+    /// makes the store part of this operation `Relaxed`, and using `Release`
+    /// makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(1u32);
+    /// assert_eq!(x.checked_fetch_sub(1, Ordering::SeqCst), Ok(1));
+    /// assert_eq!(x.checked_fetch_sub(1, Ordering::SeqCst), Err(0));
+    ///
+    /// let refs = Atomic::new(NonZeroU32::new(1));
+    /// let res = refs.checked_fetch_sub(NonZeroU32::new(1), Ordering::SeqCst);
+    /// assert_eq!(res, Err(NonZeroU32::new(1)));
+    /// ```
+    pub fn checked_fetch_sub(&self, val: T, order: Ordering) -> Result<T, T> {
+        let val = val.pack();
+        self.fetch_checked(order, |cur| cur.checked_sub(val))
+    }
+
+    /// Adds to the current value, saturating at the numeric bounds instead
+    /// of overflowing. Returns the previous value.
+    ///
+    /// For types with [`AtomInteger::NON_ZERO`] (e.g. `Option<NonZeroI32>`),
+    /// a result of zero is replaced by `1` or `-1` (whichever has the sign of
+    /// the current value), unless the current value already is zero.
+    ///
+    /// This is a compare-exchange loop. `saturating_fetch_add` takes an
+    /// [`Ordering`] argument which describes the memory ordering of this
+    /// operation. All ordering modes are possible. Note that using `Acquire`
+    /// makes the store part of this operation `Relaxed`, and using `Release`
+    /// makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(120i8);
+    /// assert_eq!(x.saturating_fetch_add(10, Ordering::SeqCst), 120);
+    /// assert_eq!(x.load(Ordering::SeqCst), 127);
+    /// ```
+    pub fn saturating_fetch_add(&self, val: T, order: Ordering) -> T {
+        let val = val.pack();
+        self.fetch_saturating(order, |cur| cur.saturating_add(val))
+    }
+
+    /// Subtracts from the current value, saturating at the numeric bounds
+    /// instead of overflowing. Returns the previous value.
+    ///
+    /// For types with [`AtomInteger::NON_ZERO`] (e.g. `Option<NonZeroU32>`),
+    /// a result of zero is replaced by `1` or `-1` (whichever has the sign of
+    /// the current value), unless the current value already is zero.
+    ///
+    /// This is a compare-exchange loop. `saturating_fetch_sub` takes an
+    /// [`Ordering`] argument which describes the memory ordering of this
+    /// operation. All ordering modes are possible. Note that using `Acquire`
+    /// makes the store part of this operation `Relaxed`, and using `Release`
+    /// makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(3u64);
+    /// assert_eq!(x.saturating_fetch_sub(10, Ordering::SeqCst), 3);
+    /// assert_eq!(x.load(Ordering::SeqCst), 0);
+    /// ```
+    pub fn saturating_fetch_sub(&self, val: T, order: Ordering) -> T {
+        let val = val.pack();
+        self.fetch_saturating(order, |cur| cur.saturating_sub(val))
+    }
+
+    /// Shifts the current value left by `n` bits, returning the previous
+    /// value.
+    ///
+    /// Like `wrapping_shl` on integers, `n` is taken modulo the number of
+    /// bits of the representation.
+    ///
+    /// This is a compare-exchange loop. `fetch_shl` takes an [`Ordering`]
+    /// argument which describes the memory ordering of this operation. All
+    /// ordering modes are possible. Note that using `Acquire` makes the store
+    /// part of this operation `Relaxed`, and using `Release` makes the load
+    /// part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(0b0110u8);
+    /// assert_eq!(x.fetch_shl(5, Ordering::SeqCst), 0b0110);
+    /// assert_eq!(x.load(Ordering::SeqCst), 0b1100_0000);
+    /// ```
+    pub fn fetch_shl(&self, n: u32, order: Ordering) -> T {
+        T::unpack(impls::fetch_op(&self.0, order, |cur: T::Repr| cur.wrapping_shl(n)))
     }
+
+    /// Shifts the current value right by `n` bits, returning the previous
+    /// value. For signed representations, this is an arithmetic shift.
+    ///
+    /// Like `wrapping_shr` on integers, `n` is taken modulo the number of
+    /// bits of the representation.
+    ///
+    /// This is a compare-exchange loop. `fetch_shr` takes an [`Ordering`]
+    /// argument which describes the memory ordering of this operation. All
+    /// ordering modes are possible. Note that using `Acquire` makes the store
+    /// part of this operation `Relaxed`, and using `Release` makes the load
+    /// part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(-16i32);
+    /// assert_eq!(x.fetch_shr(2, Ordering::SeqCst), -16);
+    /// assert_eq!(x.load(Ordering::SeqCst), -4);
+    /// ```
+    pub fn fetch_shr(&self, n: u32, order: Ordering) -> T {
+        T::unpack(impls::fetch_op(&self.0, order, |cur: T::Repr| cur.wrapping_shr(n)))
+    }
+
+    /// Rotates the bits of the current value left by `n` bits, returning the
+    /// previous value.
+    ///
+    /// This is a compare-exchange loop. `fetch_rotate_left` takes an
+    /// [`Ordering`] argument which describes the memory ordering of this
+    /// operation. All ordering modes are possible. Note that using `Acquire`
+    /// makes the store part of this operation `Relaxed`, and using `Release`
+    /// makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(0x81u8);
+    /// assert_eq!(x.fetch_rotate_left(1, Ordering::SeqCst), 0x81);
+    /// assert_eq!(x.load(Ordering::SeqCst), 0x03);
+    /// ```
+    pub fn fetch_rotate_left(&self, n: u32, order: Ordering) -> T {
+        T::unpack(impls::fetch_op(&self.0, order, |cur: T::Repr| cur.rotate_left(n)))
+    }
+
+    /// Rotates the bits of the current value right by `n` bits, returning the
+    /// previous value.
+    ///
+    /// This is a compare-exchange loop. `fetch_rotate_right` takes an
+    /// [`Ordering`] argument which describes the memory ordering of this
+    /// operation. All ordering modes are possible. Note that using `Acquire`
+    /// makes the store part of this operation `Relaxed`, and using `Release`
+    /// makes the load part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(0x81u8);
+    /// assert_eq!(x.fetch_rotate_right(1, Ordering::SeqCst), 0x81);
+    /// assert_eq!(x.load(Ordering::SeqCst), 0xC0);
+    /// ```
+    pub fn fetch_rotate_right(&self, n: u32, order: Ordering) -> T {
+        T::unpack(impls::fetch_op(&self.0, order, |cur: T::Repr| cur.rotate_right(n)))
+    }
+
+    fn fetch_checked(
+        &self,
+        order: Ordering,
+        op: impl Fn(T::Repr) -> Option<T::Repr>,
+    ) -> Result<T, T> {
+        let f = |cur: T::Repr| {
+            op(cur).filter(|new| !(T::NON_ZERO && new.is_zero() && !cur.is_zero()))
+        };
+        impls::fetch_checked_op(&self.0, order, f)
+            .map(Atom::unpack)
+            .map_err(Atom::unpack)
+    }
+
+    fn fetch_saturating(&self, order: Ordering, op: impl Fn(T::Repr) -> T::Repr) -> T {
+        let f = |cur: T::Repr| {
+            let new = op(cur);
+            if T::NON_ZERO && new.is_zero() && !cur.is_zero() {
+                cur.unit_with_sign_of()
+            } else {
+                new
+            }
+        };
+        T::unpack(impls::fetch_op(&self.0, order, f))
+    }
+}
+
+// TODO: the `where` bound should not be necessary as the `AtomFloat` trait
+// already specifies this. Maybe we can fix this in the future.
+impl<T: AtomFloat> Atomic<T>
+where
+    T::Repr: PrimitiveAtomFloat,
+{
+    /// Replaces the current value with its absolute value, returning the
+    /// previous value.
+    ///
+    /// This clears the sign bit with a single atomic "and". `fetch_abs`
+    /// takes an [`Ordering`] argument which describes the memory ordering of
+    /// this operation. All ordering modes are possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    ///
+    /// let x = Atomic::new(-0.25f64);
+    /// assert_eq!(x.fetch_abs(Ordering::SeqCst), -0.25);
+    /// assert_eq!(x.load(Ordering::SeqCst), 0.25);
+    /// ```
+    pub fn fetch_abs(&self, order: Ordering) -> T {
+        T::unpack(T::Repr::fetch_and(&self.0, T::Repr::MAGNITUDE_BITS, order))
+    }
+}
+
+// Methods to use an `Atomic<Option<X>>` as a slot, e.g. with
+// `Option<NonNull<_>>` or `Option<NonZeroU32>`.
+impl<X> Atomic<Option<X>>
+where
+    Option<X>: Atom,
+{
+    /// Takes the value out of the atomic, leaving `None` in its place.
+    ///
+    /// This is `swap(None, order)`. `take` takes an [`Ordering`] argument
+    /// which describes the memory ordering of this operation. All ordering
+    /// modes are possible. Note that using `Acquire` makes the store part of
+    /// this operation `Relaxed`, and using `Release` makes the load part
+    /// `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    /// use std::num::NonZeroU32;
+    ///
+    /// let slot = Atomic::new(NonZeroU32::new(7));
+    /// assert_eq!(slot.take(Ordering::SeqCst), NonZeroU32::new(7));
+    /// assert_eq!(slot.take(Ordering::SeqCst), None);
+    /// ```
+    pub fn take(&self, order: Ordering) -> Option<X> {
+        self.swap(None, order)
+    }
+
+    /// Stores `Some(v)` in the atomic, returning the previous value.
+    ///
+    /// This is `swap(Some(v), order)`. `replace` takes an [`Ordering`]
+    /// argument which describes the memory ordering of this operation. All
+    /// ordering modes are possible. Note that using `Acquire` makes the store
+    /// part of this operation `Relaxed`, and using `Release` makes the load
+    /// part `Relaxed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    /// use std::num::NonZeroU32;
+    ///
+    /// let slot = Atomic::new(None);
+    /// let one = NonZeroU32::new(1).unwrap();
+    /// assert_eq!(slot.replace(one, Ordering::SeqCst), None);
+    /// assert_eq!(slot.replace(one, Ordering::SeqCst), Some(one));
+    /// ```
+    pub fn replace(&self, v: X, order: Ordering) -> Option<X> {
+        self.swap(Some(v), order)
+    }
+
+    /// Stores `Some(v)` if the atomic is currently `None`. Otherwise, `v` is
+    /// returned as error.
+    ///
+    /// `set_if_none` takes an [`Ordering`] argument which describes the memory
+    /// ordering if `v` is stored. All ordering modes are possible. The
+    /// ordering of the load when the atomic is not `None` is derived from it
+    /// as described for [`update`][Atomic::update].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    /// use std::num::NonZeroU32;
+    ///
+    /// let slot = Atomic::new(None);
+    /// let (a, b) = (NonZeroU32::new(1).unwrap(), NonZeroU32::new(2).unwrap());
+    /// assert_eq!(slot.set_if_none(a, Ordering::AcqRel), Ok(()));
+    /// assert_eq!(slot.set_if_none(b, Ordering::AcqRel), Err(b));
+    /// assert_eq!(slot.load(Ordering::SeqCst), Some(a));
+    /// ```
+    pub fn set_if_none(&self, v: X, order: Ordering) -> Result<(), X> {
+        let new = Some(v).pack();
+        let none = None.pack();
+        match <Option<X> as Atom>::Repr::compare_exchange(
+            &self.0,
+            none,
+            new,
+            order,
+            impls::load_ordering(order),
+        ) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Self::unpack_some(new)),
+        }
+    }
+
+    /// Returns the current value if it is `Some`, otherwise initializes the
+    /// atomic with the value returned by `f`.
+    ///
+    /// This is lock-free: if multiple threads race to initialize the atomic,
+    /// all of them may call `f`, but only one value is stored. All other
+    /// threads discard the value returned by their `f` and return the stored
+    /// one. This uses `Acquire` to load the value and `AcqRel` to store it, so
+    /// everything that happened before the initialization is visible to the
+    /// threads that observe the stored value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::Atomic;
+    /// use std::num::NonZeroU64;
+    ///
+    /// let id = Atomic::new(None);
+    /// let a = id.get_or_init(|| NonZeroU64::new(42).unwrap());
+    /// let b = id.get_or_init(|| unreachable!());
+    /// assert_eq!((a.get(), b.get()), (42, 42));
+    /// ```
+    pub fn get_or_init<F>(&self, f: F) -> X
+    where
+        F: FnOnce() -> X,
+    {
+        if let Some(v) = self.load(Ordering::Acquire) {
+            return v;
+        }
+
+        let new = Some(f()).pack();
+        let none = None.pack();
+        let res = <Option<X> as Atom>::Repr::compare_exchange(
+            &self.0,
+            none,
+            new,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+        match res {
+            Ok(_) => Self::unpack_some(new),
+            Err(current) => Self::unpack_some(current),
+        }
+    }
+
+    /// Returns `true` if the atomic currently contains `Some` value.
+    ///
+    /// `is_some` takes an [`Ordering`] argument which describes the memory
+    /// ordering of this operation. Possible values are `SeqCst`, `Acquire`
+    /// and `Relaxed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is `Release` or `AcqRel`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atomig::{Atomic, Ordering};
+    /// use std::ptr::NonNull;
+    ///
+    /// let mut x = 3;
+    /// let slot = Atomic::new(Some(NonNull::from(&mut x)));
+    /// assert!(slot.is_some(Ordering::SeqCst));
+    /// slot.take(Ordering::SeqCst);
+    /// assert!(!slot.is_some(Ordering::SeqCst));
+    /// ```
+    pub fn is_some(&self, order: Ordering) -> bool {
+        self.load(order).is_some()
+    }
+
+    fn unpack_some(repr: <Option<X> as Atom>::Repr) -> X {
+        Option::<X>::unpack(repr).expect("`None` representation in `Atomic<Option<_>>`")
+    }
+}
+
+/// Error returned by [`Atomic::try_load`] and similar methods if the atomic
+/// contains a representation that is not valid for the type. Contains the
+/// invalid representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidRepr<R>(pub R);
+
+impl<R: fmt::Debug> fmt::Display for InvalidRepr<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid atomic representation {:?}", self.0)
+    }
+}
+
+impl<R: fmt::Debug> core::error::Error for InvalidRepr<R> {}
+
+fn try_unpack<T: Atom>(src: T::Repr) -> Result<T, InvalidRepr<T::Repr>> {
+    T::try_unpack(src).ok_or(InvalidRepr(src))
 }
 
 impl<T: Atom + fmt::Debug> fmt::Debug for Atomic<T> {
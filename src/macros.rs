@@ -0,0 +1,210 @@
+//! Declarative macros, usable without the 'derive' feature.
+
+/// Creates an [`Atomic`][crate::Atomic] in const context, e.g. to initialize
+/// a `static`.
+///
+/// `Atomic::new` cannot be a `const fn` since `Atom::pack` is a trait method.
+/// This macro works with values of C-like enums defined via
+/// [`atom_enum!`][crate::atom_enum] and with values of types that derive
+/// [`Atom`][crate::Atom] and are either C-like enums or structs with one field
+/// of a primitive integer type or `bool`. It also works with the sets
+/// generated by `derive(AtomFlags)`. For all other types, use
+/// [`Atomic::from_impl`][crate::Atomic::from_impl].
+///
+/// ```
+/// use atomig::{atom_enum, atomic, Atomic, Ordering};
+///
+/// atom_enum! {
+///     #[derive(Debug, PartialEq)]
+///     #[repr(u8)]
+///     enum Mode { Idle, Busy }
+/// }
+///
+/// static MODE: Atomic<Mode> = atomic!(Mode::Idle);
+/// MODE.store(Mode::Busy, Ordering::SeqCst);
+/// assert_eq!(MODE.load(Ordering::SeqCst), Mode::Busy);
+/// ```
+#[macro_export]
+macro_rules! atomic {
+    ($value:expr $(,)?) => {
+        ($value).__atomig_new_atomic()
+    };
+}
+
+/// Defines a C-like enum and implements [`Atom`][crate::Atom] for it, like
+/// `derive(Atom)` does, but without requiring the 'derive' feature.
+///
+/// The enum needs a `#[repr(_)]` attribute with a primitive integer type,
+/// which is used as `Repr` type. All other attributes, the visibility and
+/// explicit discriminants are passed through unchanged. `unpack` panics for
+/// values that are not a discriminant of any variant, `try_unpack` returns
+/// `None` for them. Values of the enum can
+/// be used with [`atomic!`][crate::atomic].
+///
+/// ```
+/// use atomig::{atom_enum, Atom, Atomic, Ordering};
+///
+/// atom_enum! {
+///     /// The operation mode.
+///     #[derive(Debug, PartialEq)]
+///     #[repr(u8)]
+///     pub enum Mode {
+///         Idle,
+///         Read = 4,
+///         Write,
+///     }
+/// }
+///
+/// let a = Atomic::new(Mode::Idle);
+/// a.store(Mode::Write, Ordering::SeqCst);
+/// assert_eq!(Mode::unpack(4), Mode::Read);
+/// ```
+#[macro_export]
+macro_rules! atom_enum {
+    // Search for the `#[repr(_)]` attribute, collecting the ones before it.
+    (@find [$($done:tt)*] #[repr($repr:ident)] $($rest:tt)*) => {
+        $crate::atom_enum!(@impl $repr [$($done)* #[repr($repr)]] $($rest)*);
+    };
+    (@find [$($done:tt)*] #[$attr:meta] $($rest:tt)*) => {
+        $crate::atom_enum!(@find [$($done)* #[$attr]] $($rest)*);
+    };
+    (@find [$($done:tt)*] $($rest:tt)*) => {
+        compile_error!(
+            "`atom_enum!` requires a `#[repr(_)]` attribute with a primitive integer type"
+        );
+    };
+
+    (
+        @impl $repr:ident [$(#[$attr:meta])*]
+        $(#[$more_attr:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_attr:meta])*
+                $variant:ident $(= $discriminant:expr)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $(#[$more_attr])*
+        $vis enum $name {
+            $(
+                $(#[$variant_attr])*
+                $variant $(= $discriminant)?,
+            )*
+        }
+
+        impl $crate::Atom for $name {
+            type Repr = $repr;
+
+            fn pack(self) -> Self::Repr {
+                self as $repr
+            }
+            fn unpack(src: Self::Repr) -> Self {
+                match <Self as $crate::Atom>::try_unpack(src) {
+                    ::core::option::Option::Some(v) => v,
+                    ::core::option::Option::None => panic!(
+                        "invalid '{}' value '{}' for enum '{}' in `Atom::unpack`",
+                        stringify!($repr),
+                        src,
+                        stringify!($name),
+                    ),
+                }
+            }
+            // Comparing with `if`s instead of using constants named like the
+            // variants as patterns, which could shadow e.g. `None`.
+            fn try_unpack(src: Self::Repr) -> ::core::option::Option<Self> {
+                $(
+                    if src == $name::$variant as $repr {
+                        return ::core::option::Option::Some($name::$variant);
+                    }
+                )*
+                ::core::option::Option::None
+            }
+        }
+
+        impl $name {
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            pub const fn __atomig_new_atomic(self) -> $crate::Atomic<Self> {
+                $crate::Atomic::from_impl(
+                    <<$repr as $crate::impls::PrimitiveAtom>::Impl>::new(self as $repr)
+                )
+            }
+        }
+    };
+
+    ($($input:tt)*) => {
+        $crate::atom_enum!(@find [] $($input)*);
+    };
+}
+
+/// Defines a tuple struct with a single field and implements
+/// [`Atom`][crate::Atom] for it, like `derive(Atom)` does, but without
+/// requiring the 'derive' feature.
+///
+/// The field's type has to implement `Atom` and its `Repr` is used as `Repr`
+/// of the struct. After a `;`, `logic` and `integer` can be listed to also
+/// implement [`AtomLogic`][crate::AtomLogic] and
+/// [`AtomInteger`][crate::AtomInteger].
+///
+/// ```
+/// use atomig::{atom_newtype, Atomic, Ordering};
+///
+/// atom_newtype! {
+///     #[derive(Debug, PartialEq)]
+///     pub struct Port(pub u16);
+/// }
+/// atom_newtype!(#[derive(Debug, PartialEq)] struct Count(u32); logic, integer);
+///
+/// let a = Atomic::new(Port(80));
+/// a.store(Port(8080), Ordering::SeqCst);
+///
+/// let c = Atomic::new(Count(3));
+/// c.fetch_add(Count(2), Ordering::SeqCst);
+/// assert_eq!(c.load(Ordering::SeqCst), Count(5));
+/// ```
+#[macro_export]
+macro_rules! atom_newtype {
+    (@marker logic $name:ident $inner:ty) => {
+        impl $crate::AtomLogic for $name {
+            const NOT_MASK: Self::Repr = <$inner as $crate::AtomLogic>::NOT_MASK;
+        }
+    };
+    (@marker integer $name:ident $inner:ty) => {
+        impl $crate::AtomInteger for $name {
+            const NON_ZERO: bool = <$inner as $crate::AtomInteger>::NON_ZERO;
+        }
+    };
+    (@marker $other:ident $name:ident $inner:ty) => {
+        compile_error!(concat!(
+            "unknown trait `",
+            stringify!($other),
+            "` in `atom_newtype!`, expected `logic` or `integer`",
+        ));
+    };
+
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($field_vis:vis $inner:ty)
+        $(; $($marker:ident),* $(,)?)?
+    ) => {
+        $(#[$attr])*
+        $vis struct $name($field_vis $inner);
+
+        impl $crate::Atom for $name {
+            type Repr = <$inner as $crate::Atom>::Repr;
+
+            fn pack(self) -> Self::Repr {
+                <$inner as $crate::Atom>::pack(self.0)
+            }
+            fn unpack(src: Self::Repr) -> Self {
+                Self(<$inner as $crate::Atom>::unpack(src))
+            }
+            fn try_unpack(src: Self::Repr) -> ::core::option::Option<Self> {
+                <$inner as $crate::Atom>::try_unpack(src).map(Self)
+            }
+        }
+
+        $($( $crate::atom_newtype!(@marker $marker $name $inner); )*)?
+    };
+}
@@ -95,6 +95,10 @@ macro_rules! logic_tests {
             let a = Atomic::new($val0);
             assert_eq!(a.fetch_xor($val1, Ordering::SeqCst), $val0);
             assert_eq!(a.load(Ordering::SeqCst), $val0 ^ $val1);
+
+            let a = Atomic::new($val0);
+            assert_eq!(a.fetch_not(Ordering::SeqCst), $val0);
+            assert_eq!(a.load(Ordering::SeqCst), !$val0);
         }
     };
 }
@@ -110,6 +114,47 @@ macro_rules! int_tests {
             let a = Atomic::new($val0);
             assert_eq!(a.fetch_sub($val1, Ordering::SeqCst), $val0);
             assert_eq!(a.load(Ordering::SeqCst), $val0.wrapping_sub($val1));
+
+            let product = $val0.wrapping_mul($val1);
+            let a = Atomic::new($val0);
+            assert_eq!(a.fetch_mul($val1, Ordering::SeqCst), $val0);
+            assert_eq!(a.fetch_div($val0, Ordering::SeqCst), product);
+            assert_eq!(a.fetch_rem($val0, Ordering::SeqCst), product / $val0);
+            assert_eq!(a.fetch_neg(Ordering::SeqCst), product / $val0 % $val0);
+            assert_eq!(a.load(Ordering::SeqCst), (product / $val0 % $val0).wrapping_neg());
+        }
+
+        #[test]
+        fn shift_rotate() {
+            let shifted = ($val1 << 3) >> 1;
+            let a = Atomic::new($val1);
+            assert_eq!(a.fetch_shl(3, Ordering::SeqCst), $val1);
+            assert_eq!(a.fetch_shr(1, Ordering::SeqCst), $val1 << 3);
+            assert_eq!(a.fetch_rotate_left(7, Ordering::SeqCst), shifted);
+            assert_eq!(a.fetch_rotate_right(7, Ordering::SeqCst), shifted.rotate_left(7));
+            assert_eq!(a.load(Ordering::SeqCst), shifted);
+        }
+    };
+}
+
+macro_rules! float_tests {
+    ($val0:expr, $val1:expr) => {
+        #[test]
+        fn float() {
+            let a = Atomic::new($val0);
+            assert_eq!(a.fetch_add($val1, Ordering::SeqCst), $val0);
+            assert_eq!(a.fetch_sub($val0, Ordering::SeqCst), $val0 + $val1);
+            assert_eq!(a.fetch_mul($val0, Ordering::SeqCst), $val1);
+            assert_eq!(a.fetch_div($val1, Ordering::SeqCst), $val1 * $val0);
+            assert_eq!(a.fetch_rem($val1, Ordering::SeqCst), $val0);
+            assert_eq!(a.load(Ordering::SeqCst), $val0 % $val1);
+
+            let a = Atomic::new($val0);
+            assert_eq!(a.fetch_max($val1, Ordering::SeqCst), $val0);
+            assert_eq!(a.fetch_min($val0, Ordering::SeqCst), $val1);
+            assert_eq!(a.fetch_neg(Ordering::SeqCst), $val0);
+            assert_eq!(a.fetch_abs(Ordering::SeqCst), -$val0);
+            assert_eq!(a.load(Ordering::SeqCst), $val0);
         }
     };
 }
@@ -129,7 +174,7 @@ macro_rules! emit_if {
 macro_rules! gen_tests_for_primitives {
     (
         $mod_name:ident, $ty:ty, $val0:expr, $val1:expr,
-        [$with_logic:ident $with_int:ident]
+        [$with_logic:ident $with_int:ident $with_float:ident]
     ) => {
         mod $mod_name {
             use super::*;
@@ -140,45 +185,46 @@ macro_rules! gen_tests_for_primitives {
 
             emit_if!($with_logic, { logic_tests!($val0, $val1); });
             emit_if!($with_int, { int_tests!($val0, $val1); });
+            emit_if!($with_float, { float_tests!($val0, $val1); });
         }
     };
 }
 
-//                        mod     ty     val0    val1     [logic int]
-gen_tests_for_primitives!(_bool,  bool,  true,   false,   [y n]);
-gen_tests_for_primitives!(_u8,    u8,    7u8,    33u8,    [y y]);
-gen_tests_for_primitives!(_i8,    i8,    7i8,    33i8,    [y y]);
-gen_tests_for_primitives!(_u16,   u16,   7u16,   33u16,   [y y]);
-gen_tests_for_primitives!(_i16,   i16,   7i16,   33i16,   [y y]);
-gen_tests_for_primitives!(_u32,   u32,   7u32,   33u32,   [y y]);
-gen_tests_for_primitives!(_i32,   i32,   7i32,   33i32,   [y y]);
-gen_tests_for_primitives!(_u64,   u64,   7u64,   33u64,   [y y]);
-gen_tests_for_primitives!(_i64,   i64,   7i64,   33i64,   [y y]);
-gen_tests_for_primitives!(_usize, usize, 7usize, 33usize, [y y]);
-gen_tests_for_primitives!(_isize, isize, 7isize, 33isize, [y y]);
-gen_tests_for_primitives!(_f32,   f32,   7.0f32, 33.0f32, [n n]);
-gen_tests_for_primitives!(_f64,   f64,   7.0f64, 33.0f64, [n n]);
-gen_tests_for_primitives!(_char,  char,  'x',    '♥',     [n n]);
+//                        mod     ty     val0    val1     [logic int float]
+gen_tests_for_primitives!(_bool,  bool,  true,   false,   [y n n]);
+gen_tests_for_primitives!(_u8,    u8,    7u8,    33u8,    [y y n]);
+gen_tests_for_primitives!(_i8,    i8,    7i8,    33i8,    [y y n]);
+gen_tests_for_primitives!(_u16,   u16,   7u16,   33u16,   [y y n]);
+gen_tests_for_primitives!(_i16,   i16,   7i16,   33i16,   [y y n]);
+gen_tests_for_primitives!(_u32,   u32,   7u32,   33u32,   [y y n]);
+gen_tests_for_primitives!(_i32,   i32,   7i32,   33i32,   [y y n]);
+gen_tests_for_primitives!(_u64,   u64,   7u64,   33u64,   [y y n]);
+gen_tests_for_primitives!(_i64,   i64,   7i64,   33i64,   [y y n]);
+gen_tests_for_primitives!(_usize, usize, 7usize, 33usize, [y y n]);
+gen_tests_for_primitives!(_isize, isize, 7isize, 33isize, [y y n]);
+gen_tests_for_primitives!(_f32,   f32,   7.0f32, 33.0f32, [n n y]);
+gen_tests_for_primitives!(_f64,   f64,   7.0f64, 33.0f64, [n n y]);
+gen_tests_for_primitives!(_char,  char,  'x',    '♥',     [n n n]);
 
 // Arrays. They do implement `AtomLogic` but the logic tests don't work with them.
-//                        mod          ty        val0    val1     [logic int]
-gen_tests_for_primitives!(_u8array2,   [u8; 2],  [3u8, 79], [17u8, 240], [n n]);
-gen_tests_for_primitives!(_u8array4,   [u8; 4],  [3u8, 79, 13, 230], [17u8, 240, 59, 184], [n n]);
+//                        mod          ty        val0    val1     [logic int float]
+gen_tests_for_primitives!(_u8array2,   [u8; 2],  [3u8, 79], [17u8, 240], [n n n]);
+gen_tests_for_primitives!(_u8array4,   [u8; 4],  [3u8, 79, 13, 230], [17u8, 240, 59, 184], [n n n]);
 gen_tests_for_primitives!(_u8array8,   [u8; 8],  [3u8, 79, 13, 230, 4, 80, 14, 231],
-    [17u8, 240, 59, 184, 18, 241, 60, 185], [n n]);
-gen_tests_for_primitives!(_u16array2,  [u16; 2], [3u16, 257], [17u16, 9999], [n n]);
+    [17u8, 240, 59, 184, 18, 241, 60, 185], [n n n]);
+gen_tests_for_primitives!(_u16array2,  [u16; 2], [3u16, 257], [17u16, 9999], [n n n]);
 gen_tests_for_primitives!(_u16array4,  [u16; 4], [3u16, 257, 13, 230],
-    [17u16, 9999, 59, 17003], [n n]);
-gen_tests_for_primitives!(_u32array2,  [u32; 2], [3u32, 77977], [17u32, 190247], [n n]);
-gen_tests_for_primitives!(_i8array2,   [i8; 2],  [3i8, -79], [-17i8, 113], [n n]);
+    [17u16, 9999, 59, 17003], [n n n]);
+gen_tests_for_primitives!(_u32array2,  [u32; 2], [3u32, 77977], [17u32, 190247], [n n n]);
+gen_tests_for_primitives!(_i8array2,   [i8; 2],  [3i8, -79], [-17i8, 113], [n n n]);
 gen_tests_for_primitives!(_i8array4,   [i8; 4],  [3i8, -79, 13, -120],
-    [-17i8, 113, -59, -98], [n n]);
+    [-17i8, 113, -59, -98], [n n n]);
 gen_tests_for_primitives!(_i8array8,   [i8; 8],  [3i8, -79, 13, -120, 4, 80, -14, 111],
-    [-17i8, 113, -59, -98, -18, 114, 60, -128], [n n]);
-gen_tests_for_primitives!(_i16array2,  [i16; 2], [3i16, -257], [17i16, -9999], [n n]);
+    [-17i8, 113, -59, -98, -18, 114, 60, -128], [n n n]);
+gen_tests_for_primitives!(_i16array2,  [i16; 2], [3i16, -257], [17i16, -9999], [n n n]);
 gen_tests_for_primitives!(_i16array4,  [i16; 4], [3i16, -257, 13, 230],
-        [17i16, -9999, -59, 17003], [n n]);
-gen_tests_for_primitives!(_i32array2,  [i32; 2], [3i32, -77977], [-17i32, 190247], [n n]);
+        [17i16, -9999, -59, 17003], [n n n]);
+gen_tests_for_primitives!(_i32array2,  [i32; 2], [3i32, -77977], [-17i32, 190247], [n n n]);
 
 mod _ptr {
     use super::*;
@@ -249,6 +295,25 @@ macro_rules! gen_tests_for_opt_non_zeroes {
                 assert_eq!(a.fetch_sub($ty::new(27), Ordering::SeqCst), $ty::new(27));
                 assert_eq!(a.load(Ordering::SeqCst), None);
             }
+
+            #[test]
+            fn checked_saturating() {
+                let nz = $ty::new;
+                let a = Atomic::new(nz(27));
+                assert_eq!(a.checked_fetch_sub(nz(27), Ordering::SeqCst), Err(nz(27)));
+                assert_eq!(a.checked_fetch_sub(nz(20), Ordering::SeqCst), Ok(nz(27)));
+                assert_eq!(a.saturating_fetch_sub(nz(7), Ordering::SeqCst), nz(7));
+                assert_eq!(a.load(Ordering::SeqCst), nz(1));
+                assert_eq!(a.saturating_fetch_add(None, Ordering::SeqCst), nz(1));
+
+                let a = Atomic::new(None);
+                assert_eq!(a.checked_fetch_add(nz(3), Ordering::SeqCst), Ok(None));
+                assert_eq!(a.checked_fetch_add(nz(5), Ordering::SeqCst), Ok(nz(3)));
+                assert_eq!(a.load(Ordering::SeqCst), nz(8));
+
+                let a = Atomic::<Option<$ty>>::new(None);
+                assert_eq!(a.checked_fetch_sub(None, Ordering::SeqCst), Ok(None));
+            }
         }
     };
 }
@@ -269,3 +334,1482 @@ fn _atomic_is_always_send_sync<T: Atom>(a: Atomic<T>) {
 
     requires_send_sync(a);
 }
+
+mod checked_saturating {
+    use super::*;
+    use std::num::NonZeroI8;
+
+    #[test]
+    fn unsigned() {
+        let a = Atomic::new(250u8);
+        assert_eq!(a.checked_fetch_add(5, Ordering::SeqCst), Ok(250));
+        assert_eq!(a.checked_fetch_add(1, Ordering::SeqCst), Err(255));
+        assert_eq!(a.saturating_fetch_add(100, Ordering::SeqCst), 255);
+        assert_eq!(a.load(Ordering::SeqCst), 255);
+
+        let a = Atomic::new(3usize);
+        assert_eq!(a.checked_fetch_sub(4, Ordering::SeqCst), Err(3));
+        assert_eq!(a.saturating_fetch_sub(4, Ordering::SeqCst), 3);
+        assert_eq!(a.checked_fetch_sub(0, Ordering::SeqCst), Ok(0));
+    }
+
+    #[test]
+    fn signed() {
+        let a = Atomic::new(-120i8);
+        assert_eq!(a.checked_fetch_sub(9, Ordering::SeqCst), Err(-120));
+        assert_eq!(a.saturating_fetch_sub(9, Ordering::SeqCst), -120);
+        assert_eq!(a.load(Ordering::SeqCst), i8::MIN);
+        assert_eq!(a.checked_fetch_add(-1, Ordering::SeqCst), Err(i8::MIN));
+
+        let nz = NonZeroI8::new;
+        let a = Atomic::new(nz(-2));
+        assert_eq!(a.saturating_fetch_add(nz(2), Ordering::SeqCst), nz(-2));
+        assert_eq!(a.load(Ordering::SeqCst), nz(-1));
+        assert_eq!(a.checked_fetch_add(nz(1), Ordering::SeqCst), Err(nz(-1)));
+        assert_eq!(a.checked_fetch_add(nz(3), Ordering::SeqCst), Ok(nz(-1)));
+        assert_eq!(a.load(Ordering::SeqCst), nz(2));
+    }
+}
+
+mod read_modify_write {
+    use super::*;
+
+    #[test]
+    fn integer() {
+        let a = Atomic::new(-7i32);
+        assert_eq!(a.fetch_rem(4, Ordering::SeqCst), -7);
+        assert_eq!(a.fetch_neg(Ordering::SeqCst), -3);
+        assert_eq!(a.fetch_shl(33, Ordering::SeqCst), 3);
+        assert_eq!(a.fetch_shr(1, Ordering::SeqCst), 6);
+        assert_eq!(a.load(Ordering::SeqCst), 3);
+
+        let a = Atomic::new(i8::MIN);
+        assert_eq!(a.fetch_neg(Ordering::SeqCst), i8::MIN);
+        assert_eq!(a.fetch_div(-1, Ordering::SeqCst), i8::MIN);
+        assert_eq!(a.load(Ordering::SeqCst), i8::MIN);
+
+        let a = Atomic::new(0b1000_0001u8);
+        assert_eq!(a.fetch_rotate_left(9, Ordering::SeqCst), 0b1000_0001);
+        assert_eq!(a.fetch_not(Ordering::SeqCst), 0b0000_0011);
+        assert_eq!(a.load(Ordering::SeqCst), 0b1111_1100);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to divide by zero")]
+    fn div_by_zero() {
+        Atomic::new(3u16).fetch_div(0, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn bool_not() {
+        let a = Atomic::new(false);
+        assert!(!a.fetch_not(Ordering::SeqCst));
+        assert!(a.fetch_not(Ordering::SeqCst));
+        assert!(!a.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn float() {
+        let a = Atomic::new(7.5f64);
+        assert_eq!(a.fetch_rem(2.0, Ordering::SeqCst), 7.5);
+        assert_eq!(a.fetch_div(0.0, Ordering::SeqCst), 1.5);
+        assert_eq!(a.fetch_neg(Ordering::SeqCst), f64::INFINITY);
+        assert_eq!(a.load(Ordering::SeqCst), f64::NEG_INFINITY);
+
+        let a = Atomic::new(crate::TotalF64(-0.0));
+        assert_eq!(a.fetch_neg(Ordering::SeqCst), crate::TotalF64(-0.0));
+        assert_eq!(a.fetch_mul(crate::TotalF64(-2.0), Ordering::SeqCst), crate::TotalF64(0.0));
+        assert_eq!(a.load(Ordering::SeqCst), crate::TotalF64(-0.0));
+    }
+}
+
+mod bit_ops {
+    use super::*;
+
+    #[test]
+    fn claim_slots() {
+        let slots = Atomic::new(0u64);
+        for n in 0..64 {
+            assert!(!slots.fetch_bit_set(n, Ordering::SeqCst));
+            assert!(slots.bit_test(n, Ordering::SeqCst));
+        }
+        assert_eq!(slots.load(Ordering::SeqCst), u64::MAX);
+        assert!(slots.fetch_bit_clear(63, Ordering::SeqCst));
+        assert!(!slots.fetch_bit_clear(63, Ordering::SeqCst));
+        assert_eq!(slots.load(Ordering::SeqCst), u64::MAX >> 1);
+    }
+
+    #[test]
+    fn signed_and_bool() {
+        let a = Atomic::new(0i8);
+        assert!(!a.fetch_bit_toggle(7, Ordering::SeqCst));
+        assert_eq!(a.load(Ordering::SeqCst), i8::MIN);
+        assert!(a.fetch_bit_clear(7, Ordering::SeqCst));
+        assert_eq!(a.load(Ordering::SeqCst), 0);
+
+        let a = Atomic::new(false);
+        assert!(!a.fetch_bit_set(0, Ordering::SeqCst));
+        assert!(a.fetch_bit_toggle(0, Ordering::SeqCst));
+        assert!(!a.bit_test(0, Ordering::SeqCst));
+    }
+
+    #[test]
+    #[should_panic(expected = "bit index 16 out of range in `Atomic::fetch_bit_set`")]
+    fn out_of_range() {
+        Atomic::new(0u16).fetch_bit_set(16, Ordering::SeqCst);
+    }
+}
+
+mod masked {
+    use super::*;
+
+    #[test]
+    fn fetch_store_masked() {
+        let a = Atomic::new(-1i16);
+        assert_eq!(a.fetch_store_masked(0x00F0, 0x1234, Ordering::SeqCst), -1);
+        assert_eq!(a.load(Ordering::SeqCst), 0xFF3Fu16 as i16);
+        assert_eq!(a.fetch_store_masked(0, 0, Ordering::SeqCst), 0xFF3Fu16 as i16);
+        assert_eq!(a.fetch_store_masked(-1, 7, Ordering::SeqCst), 0xFF3Fu16 as i16);
+        assert_eq!(a.load(Ordering::SeqCst), 7);
+
+        let a = Atomic::new(true);
+        assert!(a.fetch_store_masked(false, false, Ordering::SeqCst));
+        assert!(a.fetch_store_masked(true, false, Ordering::SeqCst));
+        assert!(!a.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn compare_exchange_masked() {
+        let a = Atomic::new(0x1200u32);
+        let cas = |current, new| {
+            a.compare_exchange_masked(0xFF, current, new, Ordering::SeqCst, Ordering::SeqCst)
+        };
+        assert_eq!(cas(0xAB00, 0xCD05), Ok(0x1200));
+        assert_eq!(a.load(Ordering::SeqCst), 0x1205);
+        assert_eq!(cas(0x04, 0x06), Err(0x1205));
+        assert_eq!(cas(0x05, 0x06), Ok(0x1205));
+        assert_eq!(a.load(Ordering::SeqCst), 0x1206);
+    }
+}
+
+mod compare_exchange_eq {
+    use super::*;
+
+    #[test]
+    fn float() {
+        let a = Atomic::new(0.0f32);
+        let res = a.compare_exchange_eq(-0.0, 2.0, Ordering::SeqCst, Ordering::SeqCst);
+        assert_eq!(res.map(f32::to_bits), Ok(0));
+        assert_eq!(a.compare_exchange_eq(1.0, 3.0, Ordering::SeqCst, Ordering::SeqCst), Err(2.0));
+
+        let nan = f32::from_bits(f32::NAN.to_bits() | 1);
+        a.store(nan, Ordering::SeqCst);
+        assert!(a.compare_exchange_eq(nan, 3.0, Ordering::SeqCst, Ordering::SeqCst).is_err());
+        let both_nan = |a: &f32, b: &f32| a.is_nan() && b.is_nan();
+        let order = Ordering::SeqCst;
+        let res = a.compare_exchange_by(f32::NAN, 3.0, both_nan, order, order);
+        assert_eq!(res.map(f32::to_bits), Ok(nan.to_bits()));
+        assert_eq!(a.load(Ordering::SeqCst), 3.0);
+    }
+
+    #[test]
+    fn non_canonical() {
+        // Only the lowest bit is significant.
+        #[derive(Debug)]
+        struct Parity(u8);
+        impl Atom for Parity {
+            type Repr = u8;
+            fn pack(self) -> u8 {
+                self.0
+            }
+            fn unpack(src: u8) -> Self {
+                Self(src)
+            }
+        }
+        impl PartialEq for Parity {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 % 2 == other.0 % 2
+            }
+        }
+
+        let a = Atomic::new(Parity(7));
+        let res = a.compare_exchange_eq(Parity(1), Parity(4), Ordering::SeqCst, Ordering::SeqCst);
+        assert_eq!(res.map(|p| p.0), Ok(7));
+        let res = a.compare_exchange_eq(Parity(3), Parity(5), Ordering::SeqCst, Ordering::SeqCst);
+        assert_eq!(res.map_err(|p| p.0), Err(4));
+    }
+}
+
+mod update {
+    use super::*;
+
+    #[test]
+    fn orderings() {
+        use Ordering::*;
+
+        let a = Atomic::new('a');
+        for order in [Relaxed, Release, Acquire, AcqRel, SeqCst] {
+            let (old, new) = a.update(order, |c| (c as u8 + 1) as char);
+            assert_eq!(new as u32, old as u32 + 1);
+            assert_eq!(a.try_update(order, |_| None), Err(new));
+            assert_eq!(a.store_if(|&c| c == new, new, order), Ok(new));
+        }
+        assert_eq!(a.update_and_fetch(Release, |c| c.to_ascii_uppercase()), 'F');
+        assert_eq!(a.load(SeqCst), 'F');
+    }
+
+    #[test]
+    fn non_copy() {
+        #[derive(Debug, PartialEq)]
+        struct Count(u32);
+        impl Atom for Count {
+            type Repr = u32;
+            fn pack(self) -> u32 {
+                self.0
+            }
+            fn unpack(src: u32) -> Self {
+                Self(src)
+            }
+        }
+
+        let a = Atomic::new(Count(1));
+        assert_eq!(a.update(Ordering::SeqCst, |c| Count(c.0 * 10)), (Count(1), Count(10)));
+        assert_eq!(a.try_update(Ordering::SeqCst, |c| Some(Count(c.0 + 1))), Ok(Count(10)));
+        assert_eq!(a.store_if(|c| c.0 == 10, Count(0), Ordering::SeqCst), Err(Count(11)));
+        assert_eq!(a.into_inner(), Count(11));
+    }
+}
+
+mod max_min_by {
+    use super::*;
+    use crate::atom_enum;
+
+    atom_enum! {
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+        #[repr(u8)]
+        enum Severity { Info, Warn, Error }
+    }
+
+    #[test]
+    fn ord() {
+        let a = Atomic::new(Severity::Info);
+        let max = |v| a.fetch_max_by(v, Ord::cmp, Ordering::SeqCst);
+        assert_eq!(max(Severity::Error), Severity::Info);
+        assert_eq!(max(Severity::Warn), Severity::Error);
+        assert_eq!(a.load(Ordering::SeqCst), Severity::Error);
+
+        let min = |v| a.fetch_min_by(v, Ord::cmp, Ordering::SeqCst);
+        assert_eq!(min(Severity::Warn), Severity::Error);
+        assert_eq!(min(Severity::Info), Severity::Warn);
+        assert_eq!(min(Severity::Error), Severity::Info);
+        assert_eq!(a.load(Ordering::SeqCst), Severity::Info);
+    }
+
+    #[test]
+    fn ord_shorthands() {
+        let a = Atomic::new(Severity::Warn);
+        assert_eq!(a.fetch_min_ord(Severity::Error, Ordering::SeqCst), Severity::Warn);
+        assert_eq!(a.fetch_max_ord(Severity::Error, Ordering::SeqCst), Severity::Warn);
+        assert_eq!(a.fetch_max_ord(Severity::Info, Ordering::SeqCst), Severity::Error);
+        assert_eq!(a.fetch_min_ord(Severity::Info, Ordering::SeqCst), Severity::Error);
+        assert_eq!(a.load(Ordering::SeqCst), Severity::Info);
+    }
+
+    #[test]
+    fn by_key() {
+        let a = Atomic::new(0x3_01u16);
+        let low = |v: &u16| *v as u8;
+        assert_eq!(a.fetch_min_by_key(0x1_02, low, Ordering::SeqCst), 0x3_01);
+        assert_eq!(a.fetch_max_by_key(0x1_01, low, Ordering::SeqCst), 0x3_01);
+        assert_eq!(a.fetch_max_by_key(0x0_09, low, Ordering::SeqCst), 0x3_01);
+        assert_eq!(a.load(Ordering::SeqCst), 0x0_09);
+    }
+}
+
+mod option {
+    use super::*;
+    use std::{num::NonZeroU16, ptr::NonNull, sync::Arc, thread, vec::Vec};
+
+    #[test]
+    fn slot() {
+        let nz = |v| NonZeroU16::new(v).unwrap();
+        let a = Atomic::new(None);
+        assert!(!a.is_some(Ordering::SeqCst));
+        assert_eq!(a.take(Ordering::SeqCst), None);
+        assert_eq!(a.set_if_none(nz(3), Ordering::Release), Ok(()));
+        assert_eq!(a.set_if_none(nz(4), Ordering::Release), Err(nz(4)));
+        assert_eq!(a.replace(nz(5), Ordering::SeqCst), Some(nz(3)));
+        assert_eq!(a.get_or_init(|| nz(6)), nz(5));
+        assert_eq!(a.take(Ordering::SeqCst), Some(nz(5)));
+        assert_eq!(a.get_or_init(|| nz(6)), nz(6));
+        assert!(a.is_some(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn pointer() {
+        let mut values = [1u8, 2];
+        let (first, second) = values.split_at_mut(1);
+        let (p0, p1) = (NonNull::from(&mut first[0]), NonNull::from(&mut second[0]));
+        let a = Atomic::new(Some(p0));
+        assert_eq!(a.set_if_none(p1, Ordering::SeqCst), Err(p1));
+        assert_eq!(a.take(Ordering::SeqCst), Some(p0));
+        assert_eq!(a.get_or_init(|| p1), p1);
+    }
+
+    #[test]
+    fn get_or_init_race() {
+        let a = Arc::new(Atomic::<Option<NonZeroU16>>::new(None));
+        let handles = (1..=8)
+            .map(|i| {
+                let a = a.clone();
+                thread::spawn(move || a.get_or_init(|| NonZeroU16::new(i).unwrap()))
+            })
+            .collect::<Vec<_>>();
+        let results = handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>();
+        let winner = a.load(Ordering::SeqCst).unwrap();
+        assert!(results.iter().all(|&v| v == winner));
+    }
+}
+
+mod float {
+    use super::*;
+
+    #[test]
+    fn signed_zero() {
+        let a = Atomic::new(-0.0f64);
+        assert_eq!(a.fetch_max(0.0, Ordering::SeqCst).to_bits(), (-0.0f64).to_bits());
+        assert_eq!(a.load(Ordering::SeqCst).to_bits(), 0.0f64.to_bits());
+        a.fetch_min(-0.0, Ordering::SeqCst);
+        assert_eq!(a.load(Ordering::SeqCst).to_bits(), (-0.0f64).to_bits());
+        a.fetch_abs(Ordering::SeqCst);
+        assert_eq!(a.load(Ordering::SeqCst).to_bits(), 0.0f64.to_bits());
+    }
+
+    #[test]
+    fn nan() {
+        let a = Atomic::new(3.0f32);
+        a.fetch_max(f32::NAN, Ordering::SeqCst);
+        assert!(a.load(Ordering::SeqCst).is_nan());
+        a.fetch_min(1.0, Ordering::SeqCst);
+        assert!(a.load(Ordering::SeqCst).is_nan());
+        a.fetch_neg(Ordering::SeqCst);
+        assert!(a.load(Ordering::SeqCst).is_nan());
+
+        let a = Atomic::new(f32::NAN);
+        a.fetch_add(1.0, Ordering::SeqCst);
+        assert!(a.load(Ordering::SeqCst).is_nan());
+    }
+}
+
+mod total_float {
+    use super::*;
+    use crate::{TotalF32, TotalF64};
+
+    mod _f32 {
+        use super::*;
+        generic_tests!(TotalF32, TotalF32(7.0), TotalF32(-33.0));
+        default_tests!(TotalF32);
+    }
+
+    mod _f64 {
+        use super::*;
+        generic_tests!(TotalF64, TotalF64(7.0), TotalF64(-33.0));
+        default_tests!(TotalF64);
+    }
+
+    #[test]
+    fn repr_order() {
+        let sorted = [
+            -f64::NAN, f64::NEG_INFINITY, -1.5, -f64::MIN_POSITIVE, -0.0,
+            0.0, 5e-324, 1.0, f64::MAX, f64::INFINITY, f64::NAN,
+        ];
+        for w in sorted.windows(2) {
+            assert!(TotalF64(w[0]).pack() < TotalF64(w[1]).pack(), "{:?}", w);
+            assert!(TotalF64(w[0]) < TotalF64(w[1]));
+        }
+        for f in sorted {
+            assert_eq!(TotalF64::unpack(TotalF64(f).pack()).0.to_bits(), f.to_bits());
+            assert_eq!(TotalF32::unpack(TotalF32(f as f32).pack()), TotalF32(f as f32));
+        }
+    }
+
+    #[test]
+    fn atomic() {
+        let a = Atomic::new(TotalF64(-0.0));
+        assert_eq!(a.fetch_max(TotalF64(0.0), Ordering::SeqCst), TotalF64(-0.0));
+        assert_eq!(a.fetch_min(TotalF64(-2.5), Ordering::SeqCst), TotalF64(0.0));
+        assert_eq!(a.fetch_add(TotalF64(1.0), Ordering::SeqCst), TotalF64(-2.5));
+        assert_eq!(a.fetch_sub(TotalF64(0.5), Ordering::SeqCst), TotalF64(-1.5));
+        assert_eq!(a.fetch_max(TotalF64(f64::NAN), Ordering::SeqCst), TotalF64(-2.0));
+        assert!(a.load(Ordering::SeqCst).0.is_nan());
+
+        let a = Atomic::new(TotalF32(3.0));
+        a.fetch_max(TotalF32(f32::INFINITY), Ordering::Relaxed);
+        a.fetch_min(TotalF32(-f32::NAN), Ordering::Relaxed);
+        assert_eq!(a.load(Ordering::Relaxed), TotalF32(-f32::NAN));
+    }
+}
+
+mod try_unpack {
+    use super::*;
+    use crate::InvalidRepr;
+    use std::{
+        num::{NonZeroU32, Wrapping},
+        sync::atomic::AtomicU32,
+    };
+
+    #[test]
+    fn builtin() {
+        assert_eq!(char::try_unpack(0x61), Some('a'));
+        assert_eq!(char::try_unpack(0xD800), None);
+        assert_eq!(NonZeroU32::try_unpack(0), None);
+        assert_eq!(Wrapping::<NonZeroU32>::try_unpack(3), NonZeroU32::new(3).map(Wrapping));
+        assert_eq!(Foo::try_unpack(0x01FF), Some(Foo::Nothing));
+    }
+
+    #[test]
+    fn atomic() {
+        let a: Atomic<char> = Atomic::from_impl(AtomicU32::new(0xD800));
+        assert_eq!(a.try_load(Ordering::SeqCst), Err(InvalidRepr(0xD800)));
+        assert_eq!(
+            a.try_compare_exchange('a', 'b', Ordering::SeqCst, Ordering::SeqCst),
+            Err(InvalidRepr(0xD800)),
+        );
+        assert_eq!(a.try_swap('x', Ordering::SeqCst), Err(InvalidRepr(0xD800)));
+        assert_eq!(a.try_load(Ordering::SeqCst), Ok('x'));
+        assert_eq!(
+            a.try_compare_exchange('x', 'y', Ordering::SeqCst, Ordering::SeqCst),
+            Ok(Ok('x')),
+        );
+        assert_eq!(a.try_swap('z', Ordering::SeqCst), Ok('y'));
+    }
+
+    #[test]
+    fn error() {
+        fn load(a: &Atomic<char>) -> Result<char, std::boxed::Box<dyn std::error::Error>> {
+            Ok(a.try_load(Ordering::SeqCst)?)
+        }
+
+        let a: Atomic<char> = Atomic::from_impl(AtomicU32::new(0xD800));
+        let err = load(&a).unwrap_err();
+        assert_eq!(std::string::ToString::to_string(&err), "invalid atomic representation 55296");
+        a.store('a', Ordering::SeqCst);
+        assert_eq!(load(&a).ok(), Some('a'));
+    }
+}
+
+mod macros {
+    use super::*;
+    use crate::{atom_enum, atom_newtype, atomic};
+
+    atom_enum! {
+        /// Docs before the `repr` attribute.
+        #[derive(Debug, PartialEq)]
+        #[repr(i16)]
+        enum Mode {
+            Idle = -1,
+            #[allow(dead_code)]
+            Read,
+            Write = 300,
+        }
+    }
+
+    atom_newtype! {
+        #[derive(Debug, PartialEq, Default)]
+        struct Port(u16);
+    }
+
+    atom_newtype!(#[derive(Debug, PartialEq)] struct Count(pub u8); logic, integer);
+
+    static MODE: Atomic<Mode> = atomic!(Mode::Write);
+
+    mod mode {
+        use super::*;
+        generic_tests!(Mode, Mode::Read, Mode::Write);
+    }
+
+    generic_tests!(Port, Port(80), Port(8080));
+    default_tests!(Port);
+
+    #[test]
+    fn enum_repr() {
+        assert_eq!(Mode::Idle.pack(), -1);
+        assert_eq!(Mode::unpack(0), Mode::Read);
+        assert_eq!(Mode::unpack(300), Mode::Write);
+        assert_eq!(MODE.load(Ordering::SeqCst), Mode::Write);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid 'i16' value '1' for enum 'Mode'")]
+    fn enum_invalid() {
+        Mode::unpack(1);
+    }
+
+    #[test]
+    fn try_unpack() {
+        assert_eq!(Mode::try_unpack(300), Some(Mode::Write));
+        assert_eq!(Mode::try_unpack(1), None);
+        assert_eq!(Port::try_unpack(443), Some(Port(443)));
+    }
+
+    #[test]
+    fn newtype_non_zero() {
+        use std::num::NonZeroU8;
+
+        atom_newtype!(#[derive(Debug, PartialEq)] struct Slots(Option<NonZeroU8>); integer);
+
+        let two = || Slots(NonZeroU8::new(2));
+        let a = Atomic::new(two());
+        assert_eq!(a.checked_fetch_sub(two(), Ordering::SeqCst), Err(two()));
+        assert_eq!(a.load(Ordering::SeqCst), two());
+    }
+
+    #[test]
+    fn prelude_variant_names() {
+        atom_enum! {
+            #[derive(Debug, PartialEq)]
+            #[repr(u8)]
+            enum Access { None, Some, Ok, Err }
+        }
+
+        assert_eq!(Access::try_unpack(0), Some(Access::None));
+        assert_eq!(Access::unpack(1), Access::Some);
+        assert_eq!(Access::try_unpack(3), Some(Access::Err));
+        assert_eq!(Access::try_unpack(4), None);
+        assert_eq!(Atomic::new(Access::Ok).load(Ordering::SeqCst), Access::Ok);
+    }
+
+    mod glob_imported_variants {
+        use super::*;
+
+        atom_enum! {
+            #[derive(Debug, PartialEq)]
+            #[repr(u8)]
+            pub enum Access { None, Some, Ok, Err }
+        }
+        pub use self::Access::*;
+
+        #[test]
+        fn unpack() {
+            assert_eq!(Access::unpack(1), Some);
+            assert_eq!(Atomic::new(Err).load(Ordering::SeqCst), Err);
+        }
+
+        #[cfg(feature = "derive")]
+        mod derive {
+            use crate::{Atom, Atomic, Ordering};
+
+            #[derive(Debug, PartialEq, Atom)]
+            #[repr(u8)]
+            pub enum Access { None, Some, Ok, Err }
+            pub use self::Access::*;
+
+            #[derive(Debug, PartialEq, Atom)]
+            pub enum Slot { Empty, Full(u8) }
+            pub use self::Slot::*;
+
+            #[derive(Debug, PartialEq, Atom)]
+            #[atom(repr = u8, via = "try_from")]
+            pub enum Code { Ok = 0, Err = 1 }
+
+            impl From<Code> for u8 {
+                fn from(code: Code) -> u8 {
+                    code as u8
+                }
+            }
+            impl TryFrom<u8> for Code {
+                type Error = ();
+                fn try_from(src: u8) -> Result<Self, ()> {
+                    match src {
+                        0 => Result::Ok(Code::Ok),
+                        1 => Result::Ok(Code::Err),
+                        _ => Result::Err(()),
+                    }
+                }
+            }
+
+            #[test]
+            fn unpack() {
+                assert_eq!(Access::unpack(1), Some);
+                assert_eq!(Atomic::new(Err).load(Ordering::SeqCst), Err);
+                assert_eq!(Slot::unpack(Full(7).pack()), Full(7));
+                assert_eq!(Code::unpack(1), Code::Err);
+            }
+        }
+    }
+
+    #[test]
+    fn newtype_ops() {
+        let a = Atomic::new(Count(0b0110));
+        assert_eq!(a.fetch_and(Count(0b0011), Ordering::SeqCst), Count(0b0110));
+        assert_eq!(a.fetch_add(Count(1), Ordering::SeqCst), Count(0b0010));
+        assert_eq!(a.load(Ordering::SeqCst), Count(3));
+    }
+}
+
+#[cfg(feature = "derive")]
+mod derive {
+    use super::*;
+    use crate::AtomLogic;
+
+    mod generic_struct {
+        use super::*;
+        use crate::AtomInteger;
+        use std::marker::PhantomData;
+
+        #[derive(Debug, PartialEq, Default, Atom, AtomLogic, AtomInteger)]
+        struct Tagged<T>(T);
+
+        #[derive(Debug, PartialEq, Default)]
+        struct User;
+
+        #[derive(Debug, PartialEq, Default, Atom, AtomInteger)]
+        struct Id<T> {
+            raw: u32,
+            _marker: PhantomData<T>,
+        }
+
+        #[derive(Debug, PartialEq, Default, Atom)]
+        struct Pair(u8, PhantomData<fn()>, u8);
+
+        generic_tests!(Tagged<u16>, Tagged(3), Tagged(7));
+        default_tests!(Tagged<u16>);
+
+        #[test]
+        fn ops() {
+            let a = Atomic::new(Tagged(0b1100u8));
+            assert_eq!(a.fetch_or(Tagged(0b0011), Ordering::SeqCst), Tagged(0b1100));
+            assert_eq!(a.fetch_add(Tagged(1), Ordering::SeqCst), Tagged(0b1111));
+            assert_eq!(a.load(Ordering::SeqCst), Tagged(0b10000));
+
+            let id = |raw| Id::<User> { raw, _marker: PhantomData };
+            let a = Atomic::new(id(7));
+            assert_eq!(a.fetch_add(id(3), Ordering::SeqCst), id(7));
+            assert_eq!(a.load(Ordering::SeqCst), id(10));
+        }
+
+        #[test]
+        fn phantom_fields() {
+            assert_eq!(Pair(1, PhantomData, 2).pack(), 0x0201u16);
+            assert_eq!(Pair::unpack(0x0403), Pair(3, PhantomData, 4));
+            assert_eq!(Atomic::new(Pair(5, PhantomData, 6)).load_2(Ordering::SeqCst), 6);
+        }
+    }
+
+    mod packed_struct {
+        use super::*;
+
+        #[derive(Debug, PartialEq, Default, Atom)]
+        struct Slot {
+            gen: u16,
+            idx: u16,
+            state: u8,
+        }
+
+        #[derive(Debug, PartialEq, Default, Atom)]
+        struct Flags(bool, i8, bool);
+
+        #[derive(Debug, PartialEq, Atom)]
+        struct Glyph { c: char, width: u8 }
+
+        #[derive(Debug, PartialEq, Atom)]
+        pub(crate) struct Header { pub len: u16, pub(crate) kind: u8 }
+
+        generic_tests!(Slot, Slot { gen: 1, idx: 2, state: 3 },
+            Slot { gen: u16::MAX, idx: 0, state: u8::MAX });
+        default_tests!(Slot);
+
+        #[test]
+        fn repr() {
+            assert_eq!(Slot { gen: 0x1234, idx: 0x5678, state: 0x9A }.pack(), 0x9A_5678_1234u64);
+            assert_eq!(Flags(true, -1, true).pack(), 0b11_1111_1111u16);
+            assert_eq!(Flags::unpack(0b01_0000_0001), Flags(true, -128, false));
+        }
+
+        #[test]
+        fn try_unpack() {
+            assert_eq!(Glyph::try_unpack(0x02_0000_0061), Some(Glyph { c: 'a', width: 2 }));
+            assert_eq!(Glyph::try_unpack(0x02_0000_D800), None);
+        }
+
+        #[test]
+        fn field_helpers() {
+            let a = Atomic::new(Slot { gen: 7, idx: 2, state: 3 });
+            assert_eq!(a.load_gen(Ordering::SeqCst), 7);
+            assert_eq!(a.load_state(Ordering::SeqCst), 3);
+
+            let res = a.fetch_update_idx(Ordering::SeqCst, Ordering::SeqCst, |i| Some(i * 10));
+            assert_eq!(res, Ok(2));
+            let res = a.fetch_update_state(Ordering::SeqCst, Ordering::SeqCst, |_| None);
+            assert_eq!(res, Err(3));
+            assert_eq!(a.load(Ordering::SeqCst), Slot { gen: 7, idx: 20, state: 3 });
+
+            let a = Atomic::new(Flags(false, 5, true));
+            assert_eq!(a.load_1(Ordering::SeqCst), 5);
+            let res = a.fetch_update_0(Ordering::SeqCst, Ordering::SeqCst, |b| Some(!b));
+            assert_eq!(res, Ok(false));
+            assert_eq!(a.load(Ordering::SeqCst), Flags(true, 5, true));
+
+            let a = Atomic::new(Header { len: 12, kind: 1 });
+            assert_eq!(a.load_len(Ordering::SeqCst), 12);
+            assert_eq!(a.load_kind(Ordering::SeqCst), 1);
+        }
+    }
+
+    mod enum_repr {
+        use super::*;
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum NoRepr { A, B, C }
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum Large { A = 7, B = 300, C }
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum Negative { A = -3, B, C = 100 }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[repr(u16, align(4))]
+        enum Mixed { A, B = 0x1234 }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[repr(C)]
+        #[atom(repr = u32)]
+        enum Override { A = 1, B = 2 }
+
+        generic_tests!(NoRepr, NoRepr::B, NoRepr::C);
+
+        #[test]
+        fn repr() {
+            let _: u8 = NoRepr::A.pack();
+            assert_eq!(NoRepr::C.pack(), 2);
+            assert_eq!(Large::C.pack(), 301u16);
+            assert_eq!(Large::unpack(7), Large::A);
+            assert_eq!(Negative::B.pack(), -2i8);
+            assert_eq!(Negative::unpack(-3), Negative::A);
+            assert_eq!(Mixed::B.pack(), 0x1234u16);
+            assert_eq!(Override::B.pack(), 2u32);
+            assert_eq!(Override::unpack(1), Override::A);
+        }
+    }
+
+    mod enum_fallback {
+        use super::*;
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(fallback = Unknown)]
+        enum ConnState { Idle, Open, Closed, Unknown }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[repr(u8)]
+        enum Level {
+            Low = 1,
+            #[atom(fallback)]
+            Mid,
+            High,
+        }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[repr(i16)]
+        #[atom(fallback = "Code::from_invalid")]
+        enum Code { A = -1, B = 1 }
+
+        impl Code {
+            fn from_invalid(src: i16) -> Self {
+                if src < 0 { Code::A } else { Code::B }
+            }
+        }
+
+        #[test]
+        fn unpack() {
+            assert_eq!(ConnState::unpack(1), ConnState::Open);
+            assert_eq!(ConnState::unpack(3), ConnState::Unknown);
+            assert_eq!(ConnState::unpack(200), ConnState::Unknown);
+            assert_eq!(Level::unpack(3), Level::High);
+            assert_eq!(Level::unpack(0), Level::Mid);
+            assert_eq!(Code::unpack(-1), Code::A);
+            assert_eq!(Code::unpack(-300), Code::A);
+            assert_eq!(Code::unpack(0), Code::B);
+        }
+
+        #[test]
+        fn try_unpack() {
+            assert_eq!(ConnState::try_unpack(3), Some(ConnState::Unknown));
+            assert_eq!(ConnState::try_unpack(200), None);
+            assert_eq!(Level::try_unpack(0), None);
+            assert_eq!(Code::try_unpack(0), None);
+        }
+    }
+
+    mod enum_unpack {
+        use super::*;
+
+        const BASE: u8 = 10;
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[repr(i8)]
+        enum Offset { B = -2, A = -3, C = -1, D, E }
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum Sparse { A = 1, B = 5, C = 3, D = 200 }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[repr(u8)]
+        enum NonLiteral { A = BASE, B = BASE * 2, C }
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum Full {
+            V0, V1, V2, V3, V4, V5, V6, V7, V8, V9, V10, V11, V12, V13, V14, V15, V16, V17,
+        }
+
+        #[test]
+        fn contiguous() {
+            for (i, v) in [Offset::A, Offset::B, Offset::C, Offset::D, Offset::E].into_iter()
+                .enumerate()
+            {
+                assert_eq!(Offset::unpack(i as i8 - 3), v);
+            }
+            assert_eq!(Full::unpack(17), Full::V17);
+            assert_eq!(Full::unpack(0), Full::V0);
+        }
+
+        #[test]
+        fn sparse() {
+            assert_eq!(Sparse::unpack(1), Sparse::A);
+            assert_eq!(Sparse::unpack(3), Sparse::C);
+            assert_eq!(Sparse::unpack(5), Sparse::B);
+            assert_eq!(Sparse::unpack(200), Sparse::D);
+            assert_eq!(NonLiteral::unpack(10), NonLiteral::A);
+            assert_eq!(NonLiteral::unpack(21), NonLiteral::C);
+        }
+
+        #[test]
+        #[should_panic(expected = "invalid 'i8' value '2' for enum 'Offset'")]
+        fn contiguous_invalid_above() {
+            Offset::unpack(2);
+        }
+
+        #[test]
+        #[should_panic(expected = "invalid 'i8' value '-4' for enum 'Offset'")]
+        fn contiguous_invalid_below() {
+            Offset::unpack(-4);
+        }
+
+        #[test]
+        #[should_panic(expected = "invalid 'u8' value '4' for enum 'Sparse'")]
+        fn sparse_invalid() {
+            Sparse::unpack(4);
+        }
+
+        #[test]
+        #[should_panic(expected = "invalid 'u8' value '11' for enum 'NonLiteral'")]
+        fn non_literal_invalid() {
+            NonLiteral::unpack(11);
+        }
+
+        #[test]
+        fn try_unpack() {
+            assert_eq!(Offset::try_unpack(-1), Some(Offset::C));
+            assert_eq!(Offset::try_unpack(2), None);
+            assert_eq!(Offset::try_unpack(-4), None);
+            assert_eq!(Sparse::try_unpack(200), Some(Sparse::D));
+            assert_eq!(Sparse::try_unpack(4), None);
+            assert_eq!(NonLiteral::try_unpack(20), Some(NonLiteral::B));
+            assert_eq!(NonLiteral::try_unpack(11), None);
+        }
+    }
+
+    mod bitfield {
+        use super::*;
+
+        #[derive(Debug, PartialEq, Default, Atom)]
+        #[repr(u8)]
+        enum Mode {
+            #[default]
+            Idle,
+            Read,
+            Write,
+        }
+
+        #[derive(Debug, PartialEq, Default, Atom)]
+        #[atom(bitfield)]
+        struct LockWord {
+            locked: bool,
+            #[atom(bits = 2)]
+            mode: Mode,
+            #[atom(bits = 4)]
+            offset: i8,
+            #[atom(bits = 9)]
+            readers: u16,
+        }
+
+        #[derive(Debug, PartialEq, Clone, Copy, Atom, AtomLogic)]
+        #[atom(bitfield, repr = u8)]
+        struct Bits(bool, #[atom(bits = 6)] u8, bool);
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(bitfield)]
+        struct Tagged(#[atom(bits = 3)] u8, std::marker::PhantomData<fn()>, bool);
+
+        generic_tests!(LockWord,
+            LockWord { locked: true, mode: Mode::Write, offset: -8, readers: 511 },
+            LockWord { locked: false, mode: Mode::Read, offset: 7, readers: 3 });
+        default_tests!(LockWord);
+
+        mod logic {
+            use super::*;
+            generic_tests!(Bits, Bits(true, 5, false), Bits(false, 7, true));
+            logic_tests!(Bits(true, 5, false), Bits(false, 6, true));
+        }
+
+        impl core::ops::BitAnd for Bits {
+            type Output = Self;
+            fn bitand(self, rhs: Self) -> Self {
+                Bits(self.0 & rhs.0, self.1 & rhs.1, self.2 & rhs.2)
+            }
+        }
+        impl core::ops::BitOr for Bits {
+            type Output = Self;
+            fn bitor(self, rhs: Self) -> Self {
+                Bits(self.0 | rhs.0, self.1 | rhs.1, self.2 | rhs.2)
+            }
+        }
+        impl core::ops::BitXor for Bits {
+            type Output = Self;
+            fn bitxor(self, rhs: Self) -> Self {
+                Bits(self.0 ^ rhs.0, self.1 ^ rhs.1, self.2 ^ rhs.2)
+            }
+        }
+        impl core::ops::Not for Bits {
+            type Output = Self;
+            fn not(self) -> Self {
+                Bits(!self.0, !self.1 & 0b11_1111, !self.2)
+            }
+        }
+
+        #[test]
+        #[allow(clippy::unusual_byte_groupings)] // Grouped by field
+        fn repr() {
+            let v = || LockWord { locked: true, mode: Mode::Write, offset: -2, readers: 0x101 };
+            assert_eq!(v().pack(), 0b1_0000_0001_1110_10_1u16);
+            assert_eq!(LockWord::unpack(0b1_0000_0001_1110_10_1), v());
+            assert_eq!(Bits(true, 0b101, true).pack(), 0b1_000101_1u8);
+            assert_eq!(Tagged(5, std::marker::PhantomData, true).pack(), 0b1_101u8);
+            assert_eq!(Tagged::unpack(0b1_011), Tagged(3, std::marker::PhantomData, true));
+        }
+
+        #[test]
+        fn logic_round_trip() {
+            let a = Atomic::new(Bits(true, 0b101, false));
+            a.fetch_not(Ordering::SeqCst);
+            let current = a.load(Ordering::SeqCst);
+            assert_eq!(current, Bits(false, 0b11_1010, true));
+            let new = Bits(true, 1, true);
+            let res = a.compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst);
+            assert_eq!(res, Ok(current));
+        }
+
+        #[test]
+        #[should_panic(expected = "does not fit into its bitfield")]
+        #[cfg(debug_assertions)]
+        fn value_too_large() {
+            Bits(false, 64, false).pack();
+        }
+    }
+
+    mod data_enum {
+        use super::*;
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum Option8 { Nothing, Set(u8) }
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum ErrorCode { Timeout, Refused, Reset }
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum Task {
+            Idle,
+            Running(u32),
+            Failed { code: ErrorCode },
+            Offset(i16),
+        }
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum Single { Only(i8) }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(fallback = Invalid)]
+        enum WithFallback { A(bool), B(u16), C(u8), Invalid }
+
+        generic_tests!(Option8, Option8::Nothing, Option8::Set(0b101));
+
+        mod task {
+            use super::*;
+            generic_tests!(Task, Task::Running(u32::MAX), Task::Failed { code: ErrorCode::Reset });
+        }
+
+        #[test]
+        fn repr() {
+            assert_eq!(Option8::Nothing.pack(), 0u16);
+            assert_eq!(Option8::Set(0xFF).pack(), 0x01FF);
+            assert_eq!(Task::Idle.pack(), 0u64);
+            assert_eq!(Task::Running(7).pack(), 1 << 32 | 7);
+            assert_eq!(Task::Failed { code: ErrorCode::Refused }.pack(), 2 << 32 | 1);
+            assert_eq!(Task::Offset(-1).pack(), 3 << 32 | 0xFFFF);
+            assert_eq!(Task::unpack(3 << 32 | 0xFFFE), Task::Offset(-2));
+            assert_eq!(Single::Only(-3).pack(), -3i8 as u8);
+            assert_eq!(Single::unpack(0xFF), Single::Only(-1));
+        }
+
+        #[test]
+        fn fallback() {
+            assert_eq!(WithFallback::B(0xFFFF).pack(), 1 << 16 | 0xFFFF);
+            assert_eq!(WithFallback::unpack(2 << 16 | 3), WithFallback::C(3));
+            assert_eq!(WithFallback::unpack(3 << 16), WithFallback::Invalid);
+            assert_eq!(WithFallback::unpack(7 << 16), WithFallback::Invalid);
+        }
+
+        #[test]
+        #[should_panic(expected = "invalid tag '2' for enum 'Option8'")]
+        fn invalid_tag() {
+            Option8::unpack(0x0200);
+        }
+
+        #[test]
+        fn try_unpack() {
+            assert_eq!(Option8::try_unpack(0x0105), Some(Option8::Set(5)));
+            assert_eq!(Option8::try_unpack(0x0200), None);
+            let failed = Task::Failed { code: ErrorCode::Reset };
+            assert_eq!(Task::try_unpack(2 << 32 | 2), Some(failed));
+            assert_eq!(Task::try_unpack(2 << 32 | 3), None);
+            assert_eq!(WithFallback::try_unpack(3 << 16), Some(WithFallback::Invalid));
+            assert_eq!(WithFallback::try_unpack(7 << 16), None);
+        }
+    }
+
+    mod open_enum {
+        use super::*;
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[repr(u8)]
+        enum Status {
+            Ok = 0,
+            NotFound = 4,
+            Busy,
+            #[atom(other)]
+            Other(u8),
+        }
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum Signed {
+            A,
+            #[atom(other)]
+            Raw { value: i16 },
+            B,
+        }
+
+        generic_tests!(Status, Status::Busy, Status::Other(17));
+
+        #[test]
+        fn round_trip() {
+            for raw in 0..=u8::MAX {
+                assert_eq!(Status::unpack(raw).pack(), raw);
+            }
+            assert_eq!(Status::unpack(4), Status::NotFound);
+            assert_eq!(Status::unpack(5), Status::Busy);
+            assert_eq!(Status::unpack(1), Status::Other(1));
+            assert_eq!(Status::Other(0).pack(), 0);
+
+            assert_eq!(Signed::A.pack(), 0);
+            assert_eq!(Signed::B.pack(), 2);
+            assert_eq!(Signed::unpack(2), Signed::B);
+            assert_eq!(Signed::unpack(-1), Signed::Raw { value: -1 });
+        }
+    }
+
+    mod flags {
+        use super::*;
+        use crate::AtomFlags;
+
+        #[derive(Debug, Clone, Copy, PartialEq, AtomFlags)]
+        enum Perm { Read = 1, Write = 2, Exec = 4 }
+
+        #[derive(Debug, Clone, Copy, PartialEq, AtomFlags)]
+        #[repr(u32)]
+        enum Wide { Low = 1, High = 1 << 31 }
+
+        generic_tests!(PermSet, PermSet::from(Perm::Read), PermSet::ALL);
+        default_tests!(PermSet);
+
+        #[test]
+        fn set() {
+            let mut set = PermSet::EMPTY;
+            assert!(set.is_empty());
+            set.insert(Perm::Write);
+            set.insert(Perm::Exec);
+            assert!(set.contains(Perm::Exec));
+            assert!(!set.contains(Perm::Read));
+            set.remove(Perm::Exec);
+            assert!(set.iter().eq([Perm::Write]));
+            assert_eq!(!set, Perm::Read.into_set() | Perm::Exec.into_set());
+            assert_eq!([Perm::Read, Perm::Exec].into_iter().collect::<PermSet>().bits(), 0b101);
+            assert_eq!(PermSet::ALL.bits(), 0b111u8);
+            assert_eq!(WideSet::ALL.bits(), 0x8000_0001u32);
+        }
+
+        #[test]
+        fn atomic() {
+            let a = Atomic::new(PermSet::EMPTY);
+            assert_eq!(a.fetch_insert(Perm::Read, Ordering::SeqCst), PermSet::EMPTY);
+            a.fetch_insert(Perm::Exec, Ordering::SeqCst);
+            assert!(a.contains(Perm::Read, Ordering::SeqCst));
+            assert!(!a.contains(Perm::Write, Ordering::SeqCst));
+            assert_eq!(a.fetch_remove(Perm::Read, Ordering::SeqCst).bits(), 0b101);
+            assert_eq!(a.load(Ordering::SeqCst), PermSet::from(Perm::Exec));
+
+            let w = Atomic::new(WideSet::ALL);
+            w.fetch_remove(Wide::High, Ordering::SeqCst);
+            assert!(w.contains(Wide::Low, Ordering::SeqCst));
+            assert!(!w.contains(Wide::High, Ordering::SeqCst));
+        }
+
+        #[test]
+        fn fetch_remove_keeps_other_bits() {
+            let a = Atomic::new(PermSet::unpack(0b1000_0011));
+            assert_eq!(a.fetch_remove(Perm::Read, Ordering::SeqCst).bits(), 0b1000_0011);
+            assert_eq!(a.load(Ordering::SeqCst).bits(), 0b1000_0010);
+            assert_eq!(PermSet::try_unpack(0b1000_0010), Some(a.load(Ordering::SeqCst)));
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq, Atom, AtomLogic)]
+        struct Perms(PermSet);
+
+        #[test]
+        fn fetch_not() {
+            let a = Atomic::new(PermSet::from(Perm::Write));
+            a.fetch_not(Ordering::SeqCst);
+            let current = a.load(Ordering::SeqCst);
+            assert_eq!(current.bits(), 0b101);
+            a.fetch_not(Ordering::SeqCst);
+            a.fetch_not(Ordering::SeqCst);
+            let res = a.compare_exchange(current, PermSet::ALL, Ordering::SeqCst, Ordering::SeqCst);
+            assert_eq!(res, Ok(current));
+
+            let a = Atomic::new(Perms(PermSet::EMPTY));
+            a.fetch_not(Ordering::SeqCst);
+            assert_eq!(a.load(Ordering::SeqCst), Perms(PermSet::ALL));
+            let w = Atomic::new(std::num::Wrapping(PermSet::ALL));
+            w.fetch_not(Ordering::SeqCst);
+            assert_eq!(w.load(Ordering::SeqCst).0, PermSet::EMPTY);
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq, Atom, AtomLogic)]
+        struct Entry { perms: PermSet, owner: u8 }
+
+        #[derive(Debug, Clone, Copy, PartialEq, Atom, AtomLogic)]
+        #[atom(bitfield, repr = u8)]
+        struct Nibbles(#[atom(bits = 4)] PermSet, #[atom(bits = 4)] u8);
+
+        #[test]
+        fn fetch_not_fields() {
+            assert_eq!(<Entry as crate::AtomLogic>::NOT_MASK, 0xFF_07u16);
+            assert_eq!(<Nibbles as crate::AtomLogic>::NOT_MASK, 0xF_7u8);
+
+            let a = Atomic::new(Entry { perms: PermSet::from(Perm::Write), owner: 0x0F });
+            a.fetch_not(Ordering::SeqCst);
+            let current = a.load(Ordering::SeqCst);
+            let perms = Perm::Read.into_set() | Perm::Exec.into_set();
+            assert_eq!(current, Entry { perms, owner: 0xF0 });
+            let new = Entry { perms: PermSet::EMPTY, owner: 1 };
+            let res = a.compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst);
+            assert_eq!(res, Ok(current));
+
+            let a = Atomic::new(Nibbles(PermSet::ALL, 0b0101));
+            a.fetch_not(Ordering::SeqCst);
+            assert_eq!(a.load(Ordering::SeqCst), Nibbles(PermSet::EMPTY, 0b1010));
+        }
+    }
+
+    mod conversion {
+        use super::*;
+        use std::convert::TryFrom;
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(repr = u32, with = "rgb")]
+        struct Color { r: u8, g: u8, b: u8, name: &'static str }
+
+        mod rgb {
+            use super::Color;
+
+            pub fn pack(c: Color) -> u32 {
+                u32::from_be_bytes([0, c.r, c.g, c.b])
+            }
+            pub fn unpack(src: u32) -> Color {
+                let [_, r, g, b] = src.to_be_bytes();
+                Color { r, g, b, name: "" }
+            }
+        }
+
+        #[derive(Debug, PartialEq, Clone, Copy, Atom)]
+        #[atom(repr = u16, via = "into")]
+        struct Celsius { tenths: i16 }
+
+        impl From<Celsius> for u16 {
+            fn from(c: Celsius) -> u16 {
+                c.tenths as u16
+            }
+        }
+        impl From<u16> for Celsius {
+            fn from(src: u16) -> Self {
+                Celsius { tenths: src as i16 }
+            }
+        }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(repr = u8, via = "try_from")]
+        enum Opcode { Nop, Push, Pop }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(repr = u8, via = "try_from", fallback = Invalid)]
+        enum Lenient { Nop, Push, Pop, Invalid }
+
+        macro_rules! opcode_conversions {
+            ($ty:ident) => {
+                impl From<$ty> for u8 {
+                    fn from(op: $ty) -> u8 {
+                        op as u8
+                    }
+                }
+                impl TryFrom<u8> for $ty {
+                    type Error = ();
+                    fn try_from(src: u8) -> Result<Self, ()> {
+                        match src {
+                            0 => Ok($ty::Nop),
+                            1 => Ok($ty::Push),
+                            2 => Ok($ty::Pop),
+                            _ => Err(()),
+                        }
+                    }
+                }
+            };
+        }
+        opcode_conversions!(Opcode);
+        opcode_conversions!(Lenient);
+
+        generic_tests!(Celsius, Celsius { tenths: -40 }, Celsius { tenths: 215 });
+
+        mod opcode {
+            use super::*;
+            generic_tests!(Opcode, Opcode::Push, Opcode::Pop);
+        }
+
+        #[test]
+        fn with() {
+            let c = Color { r: 1, g: 2, b: 3, name: "navy" };
+            assert_eq!(c.pack(), 0x010203);
+            assert_eq!(Color::unpack(0x0A0B0C), Color { r: 10, g: 11, b: 12, name: "" });
+        }
+
+        #[test]
+        fn via() {
+            assert_eq!(Celsius { tenths: -1 }.pack(), u16::MAX);
+            assert_eq!(Opcode::unpack(2), Opcode::Pop);
+            assert_eq!(Lenient::unpack(1), Lenient::Push);
+            assert_eq!(Lenient::unpack(3), Lenient::Invalid);
+        }
+
+        #[test]
+        #[should_panic(expected = "invalid 'u8' value '3' for type 'Opcode'")]
+        fn via_invalid() {
+            Opcode::unpack(3);
+        }
+
+        #[test]
+        fn try_unpack() {
+            assert_eq!(Opcode::try_unpack(1), Some(Opcode::Push));
+            assert_eq!(Opcode::try_unpack(3), None);
+            assert_eq!(Lenient::try_unpack(3), None);
+            assert_eq!(Celsius::try_unpack(5), Some(Celsius { tenths: 5 }));
+        }
+    }
+
+    mod float {
+        use super::*;
+        use crate::AtomFloat;
+
+        #[derive(Debug, PartialEq, Clone, Copy, Atom, AtomFloat)]
+        struct Seconds(f64);
+
+        #[derive(Debug, PartialEq, Clone, Copy, Atom, AtomFloat)]
+        struct Gauge<T>(T);
+
+        generic_tests!(Seconds, Seconds(1.5), Seconds(-2.0));
+
+        #[test]
+        fn newtypes() {
+            let a = Atomic::new(Seconds(1.5));
+            assert_eq!(a.fetch_add(Seconds(2.0), Ordering::SeqCst), Seconds(1.5));
+            assert_eq!(a.fetch_mul(Seconds(2.0), Ordering::SeqCst), Seconds(3.5));
+            assert_eq!(a.fetch_neg(Ordering::SeqCst), Seconds(7.0));
+            assert_eq!(a.fetch_max(Seconds(-8.0), Ordering::SeqCst), Seconds(-7.0));
+            assert_eq!(a.load(Ordering::SeqCst), Seconds(-7.0));
+
+            let a = Atomic::new(Gauge(0.5f32));
+            assert_eq!(a.fetch_sub(Gauge(1.0), Ordering::SeqCst), Gauge(0.5));
+            assert_eq!(a.fetch_abs(Ordering::SeqCst), Gauge(-0.5));
+            assert_eq!(a.fetch_min(Gauge(0.25), Ordering::SeqCst), Gauge(0.5));
+            assert_eq!(a.load(Ordering::SeqCst), Gauge(0.25));
+        }
+    }
+
+    mod crate_path {
+        use super::*;
+        use crate::AtomFlags;
+
+        mod facade {
+            pub use crate as sync;
+        }
+
+        #[derive(Debug, PartialEq, Default, Atom, AtomLogic)]
+        #[atom(crate = "facade::sync")]
+        struct Mask(u8);
+
+        #[derive(Debug, PartialEq, Default, Atom)]
+        #[atom(crate = "facade::sync")]
+        struct Pair { a: u8, b: bool }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(crate = "facade::sync")]
+        enum Event { Tick, Key(char) }
+
+        #[derive(Debug, Clone, Copy, PartialEq, AtomFlags)]
+        #[atom(crate = "facade::sync")]
+        enum Perm { Read = 1, Write = 2 }
+
+        #[test]
+        fn derives() {
+            let a = Atomic::new(Mask(0b01));
+            a.fetch_or(Mask(0b10), Ordering::SeqCst);
+            assert_eq!(a.load(Ordering::SeqCst), Mask(0b11));
+            assert!(Atomic::new(Pair { a: 3, b: true }).load_b(Ordering::SeqCst));
+            assert_eq!(Atomic::new(Event::Key('x')).load(Ordering::SeqCst), Event::Key('x'));
+            assert!(Atomic::new(PermSet::ALL).contains(Perm::Write, Ordering::SeqCst));
+        }
+    }
+
+    mod non_zero {
+        use super::*;
+        use crate::AtomInteger;
+        use std::num::NonZeroU32;
+
+        #[derive(Debug, PartialEq, Atom, AtomInteger)]
+        struct RefCount(Option<NonZeroU32>);
+
+        #[test]
+        fn forwarded() {
+            let one = || RefCount(NonZeroU32::new(1));
+            let a = Atomic::new(one());
+            assert_eq!(a.checked_fetch_sub(one(), Ordering::SeqCst), Err(one()));
+            assert_eq!(a.saturating_fetch_sub(one(), Ordering::SeqCst), one());
+            assert_eq!(a.load(Ordering::SeqCst), one());
+        }
+    }
+
+    mod const_new {
+        use super::*;
+        use crate::{atomic, AtomFlags};
+
+        #[derive(Debug, PartialEq, Atom)]
+        enum Mode { Idle, Busy = 300 }
+
+        #[derive(Debug, PartialEq, Atom)]
+        struct Port(u16);
+
+        #[derive(Debug, PartialEq, Atom)]
+        struct Ready { ready: bool }
+
+        #[derive(Debug, Clone, Copy, PartialEq, AtomFlags)]
+        enum Perm { Read = 1, Write = 2 }
+
+        static MODE: Atomic<Mode> = atomic!(Mode::Busy);
+        static PORT: Atomic<Port> = atomic!(Port(8080));
+        static READY: Atomic<Ready> = atomic!(Ready { ready: true });
+        static PERMS: Atomic<PermSet> = atomic!(PermSet::ALL);
+
+        #[test]
+        fn statics() {
+            assert_eq!(MODE.load(Ordering::SeqCst), Mode::Busy);
+            MODE.store(Mode::Idle, Ordering::SeqCst);
+            assert_eq!(MODE.load(Ordering::SeqCst), Mode::Idle);
+            assert_eq!(PORT.load(Ordering::SeqCst), Port(8080));
+            assert_eq!(READY.load(Ordering::SeqCst), Ready { ready: true });
+            assert!(PERMS.contains(Perm::Write, Ordering::SeqCst));
+        }
+    }
+
+    mod niche {
+        use super::*;
+        use crate::AtomNiche;
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[repr(u8)]
+        #[atom(niche = 0xFF)]
+        enum Level { Low, Mid = 5, High }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(niche = 0)]
+        struct Port(u16);
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(niche = 0xFFFF)]
+        enum Slot { Empty, Set(u8) }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(niche = 1)]
+        enum Entry { Vacant, Occupied(u8) }
+
+        #[derive(Debug, PartialEq, Atom)]
+        #[atom(niche = u32::MAX)]
+        struct Pos { x: u8, y: u8, visible: bool }
+
+        #[test]
+        fn enum_niche() {
+            assert_eq!(Level::NICHE, 0xFF);
+            let a = Atomic::new(Some(Level::Mid));
+            assert_eq!(a.swap(None, Ordering::SeqCst), Some(Level::Mid));
+            assert_eq!(a.get_or_init(|| Level::High), Level::High);
+            assert_eq!(Option::<Level>::pack(None), 0xFF);
+            assert_eq!(Option::<Level>::try_unpack(3), None);
+            assert_eq!(Option::<Level>::try_unpack(0xFF), Some(None));
+        }
+
+        #[test]
+        fn struct_niche() {
+            let a = Atomic::new(None);
+            assert_eq!(a.replace(Port(443), Ordering::SeqCst), None);
+            assert_eq!(a.load(Ordering::SeqCst), Some(Port(443)));
+            assert_eq!(Option::<Port>::unpack(0), None);
+        }
+
+        #[test]
+        fn data_enum_niche() {
+            let a = Atomic::new(Some(Slot::Set(0xFF)));
+            assert_eq!(a.swap(Some(Slot::Empty), Ordering::SeqCst), Some(Slot::Set(0xFF)));
+            assert_eq!(a.take(Ordering::SeqCst), Some(Slot::Empty));
+            assert_eq!(Option::<Slot>::pack(None), 0xFFFF);
+
+            let a = Atomic::new(Some(Entry::Occupied(1)));
+            assert_eq!(a.swap(Some(Entry::Vacant), Ordering::SeqCst), Some(Entry::Occupied(1)));
+            assert_eq!(a.take(Ordering::SeqCst), Some(Entry::Vacant));
+            assert_eq!(a.load(Ordering::SeqCst), None);
+        }
+
+        #[test]
+        fn packed_struct_niche() {
+            let pos = Pos { x: 0xFF, y: 0xFF, visible: true };
+            let a = Atomic::new(Some(pos));
+            assert_eq!(a.take(Ordering::SeqCst), Some(Pos { x: 0xFF, y: 0xFF, visible: true }));
+            assert_eq!(a.load(Ordering::SeqCst), None);
+        }
+    }
+}